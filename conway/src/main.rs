@@ -1,17 +1,30 @@
 use eframe::egui;
 use egui::Color32;
-use std::time::{Duration, Instant};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use life_core::{cycle, grid, patterns, Boundary, BoundingBox, CancelToken, Cycle, GameOfLifeInterface, GridEngine, Rule, Simulation, SimulationHandle, SimulationThread, TGrid, Universe, UniverseLimit, GRID_START};
+use life_core::age::CellAges;
+use life_core::bitpacked_engine::BitPackedEngine;
+use life_core::cell_engine::{CellEngine, CellSchedule};
+use life_core::census::{self, CensusEntry};
+use life_core::classify::{self, Analysis, Classification};
+use life_core::closure_engine::ClosureEngine;
+use life_core::cycle::CycleDetector;
+use life_core::history::{Activity, ActivityHistory};
+use life_core::hashlife_engine::HashLife;
+use life_core::palette::RenderStyle;
+use life_core::sparse_engine::{Cell, SparseLife};
 
 mod ui;
-mod patterns;
 
-type TGrid = [[bool; 52]; 52];
-type CellFn = dyn Fn(&TGrid, &mut TGrid);
-type SmartCellFn = Box<CellFn>;
+/// Grids with more cells than this start on the bit-packed engine, one closure per cell gets too slow
+const CLOSURE_ENGINE_LIMIT: usize = 256 * 256;
 
 fn main() -> Result<(), eframe::Error> {
+    // Optional `--size WIDTHxHEIGHT`, defaults to 50x50
+    let (width, height) = grid::size_from_args();
+    
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([800.0, 950.0]),
@@ -21,161 +34,634 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Conway's Game of Life",
         options,
-        Box::new(|_cc| Box::new(GameOfLife::default())),
+        Box::new(move |cc| Box::new(GameOfLife::new(World::new(width, height), &cc.egui_ctx))),
     )
 }
 
-/// Conway's Game of Life Implementation
-/// Uses function factory approach with 2,500 specialized closures
+/// Which engine computes the generations
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EngineKind {
+    Closure,    // One closure per cell over the bounded TGrid
+    Cells,      // One resumable coroutine per cell, interleaved by a scheduler
+    BitPacked,  // 64 cells per word over the bounded TGrid
+    Sparse,     // Set of live cells, unbounded
+    HashLife,   // Memoised quadtree, unbounded, can jump 2^k generations per update
+}
+
+impl EngineKind {
+    const ALL: [EngineKind; 5] = [EngineKind::Closure, EngineKind::Cells, EngineKind::BitPacked, EngineKind::Sparse, EngineKind::HashLife];
+    
+    fn name(self) -> &'static str {
+        match self {
+            EngineKind::Closure   => "Closures (bounded)",
+            EngineKind::Cells     => "Cell coroutines (bounded)",
+            EngineKind::BitPacked => "Bit-packed (bounded)",
+            EngineKind::Sparse    => "Sparse (unbounded)",
+            EngineKind::HashLife  => "HashLife (unbounded)",
+        }
+    }
+    
+    /// Steps a `width` x `height` TGrid, only meaningful for the bounded engines
+    fn create_grid_engine(self, width: usize, height: usize, rule: Rule, boundary: Boundary, cell_schedule: CellSchedule) -> Box<dyn GridEngine> {
+        match self {
+            EngineKind::Cells     => Box::new(CellEngine::new(width, height, rule, boundary, cell_schedule)),
+            EngineKind::BitPacked => Box::new(BitPackedEngine::new(width, height, rule, boundary)),
+            _                     => Box::new(ClosureEngine::new(width, height, rule, boundary)),
+        }
+    }
+}
+
+/// Window onto an unbounded universe, the grid size doubles as the viewport size
+#[derive(Clone, Copy)]
+struct Viewport {
+    top    : i64,       // Universe row shown in the first display row
+    left   : i64,       // Universe column shown in the first display column
+    follow : bool,      // Keep the population's bounding box centred
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        // Grid cell (1, 1) sits at universe (1, 1), so switching engines keeps the picture still
+        Self { top: GRID_START as i64, left: GRID_START as i64, follow: false }
+    }
+}
+
+/// Conway's Game of Life Implementation, owned by the simulation thread
+/// Uses function factory approach with one specialized closure per cell
 /// Each cell function has baked-in coordinates and neighbor positions
-struct GameOfLife {
-    grid             : TGrid,              // 0-51 grid with border
-    next_grid        : TGrid,
-    cell_functions   : Vec<SmartCellFn>,
+/// Unbounded engines replace the grid and are drawn through the viewport
+struct World {
+    grid             : TGrid,              // Active area plus a dead border
+    engine           : Box<dyn GridEngine>,  // Steps the grid while a bounded engine is selected
+    engine_kind      : EngineKind,
+    cell_schedule    : CellSchedule,       // Task size and scheduling policy of the cell coroutine engine
+    universe         : Option<Box<dyn Universe + Send>>,  // Set while an unbounded engine is selected
+    viewport         : Viewport,
     is_running       : bool,
-    last_update      : Instant,
     update_interval  : Duration,
-    generation       : u32,
-    live_color       : Color32,
-    dead_color       : Color32,
+    generation       : u64,
+    step_log2        : u8,                 // Each update advances 2^step_log2 generations where the engine allows
     selected_pattern : usize,
-    grid_history     : [u64; 10],         // Fixed array of 10 grid hashes
-    history_count    : usize,             // Counter for circular buffer
+    rule             : Rule,
+    boundary         : Boundary,
+    cycle_detector   : CycleDetector,      // Cycles of the bounded grid, universes detect their own
+    cycle            : Option<Cycle>,      // Cycle the run has entered, which also paused it
+    analysis         : Option<Analysis>,   // What the pattern was classified as, until the cells change
+    classifying      : Option<CancelToken>,  // Set while a classification runs in the background, cancelled when the cells change
+    census           : Option<(u64, Vec<CensusEntry>)>,  // Generation the objects were counted at and their counts
+    counting         : Option<CancelToken>,  // Set while a census runs in the background, cancelled when the cells change
+    history          : Arc<ActivityHistory>,  // Population and activity since generation 0, shared with the snapshots
+    ages             : Option<Arc<CellAges>>,  // Ages of the displayed cells, tracked while the UI shades cells by them
+    handle           : Option<SimulationHandle<World>>,  // Hands background results back to the simulation thread
 }
 
-/// Conway's Game of Life Interface
-/// Defines the core functionality for game mechanics and pattern management
-trait GameOfLifeInterface {
-    // Core game mechanics
-    fn update_generation(&mut self);
-    fn hash_grid(&self) -> u64;
-    fn check_for_cycle(&mut self) -> bool;
-    
-    // Pattern management
-    fn clear_grid(&mut self);
-    fn apply_selected_pattern(&mut self);
-    
-    // Debug utilities
-    fn check_border_cells_dead(&self) -> bool;
+/// Population figures for an unbounded universe
+#[derive(Clone, Copy)]
+struct UniverseStats {
+    population : u64,
+    bounds     : Option<BoundingBox>,
+    limit      : Option<UniverseLimit>,   // Why the universe stopped advancing, if it has
 }
 
-impl Default for GameOfLife {
-    fn default() -> Self {
-        // Function factory that creates specialized functions
-        fn create_cell_fn(row: usize, col: usize) -> impl Fn(&TGrid, &mut TGrid) {
-            move |current_grid: &TGrid, next_grid: &mut TGrid| {
-                // Count live neighbors using baked-in coordinates
-                let mut count = 0;
-                
-                // Baked-in neighbor positions for this specific cell
-                let neighbors = [
-                    (row-1,col-1),(row-1,col),(row-1,col+1),(row,col-1),                    
-                    (row+1,col-1),(row+1,col),(row+1,col+1),(row,col+1)
-                ];
-                
-                for &(nr, nc) in &neighbors {  if current_grid[nr][nc] {count+=1;} }
-                
-                // Apply Conway's rules and write directly to next_grid
-                next_grid[row][col] = match (current_grid[row][col], count) {
-                    (true, 2) | (true, 3) => true,   // Survival
-                    (false, 3)            => true,   // Birth
-                    _                     => false,  // Death or stays dead
-                };
-            }
-        }
-        
-        // Create a single vector of 2500 lambda functions
-        let mut cell_functions = Vec::with_capacity(2500);
-        
-        // Initialize functions with coordinates from (1,1) to (50,50)
-        for grid_row in 1..51 {
-            for grid_col in 1..51 {
-                cell_functions.push(Box::new(create_cell_fn(grid_row, grid_col)) as SmartCellFn);
-            }
-        }
+/// What the UI draws, published by the simulation thread after every change
+struct Snapshot {
+    grid            : TGrid,                   // Active area, or the viewport's window onto the universe
+    generation      : u64,
+    is_running      : bool,
+    update_interval : Duration,
+    rule            : Rule,
+    boundary        : Boundary,
+    engine_kind     : EngineKind,
+    cell_schedule   : CellSchedule,
+    last_slices     : Option<usize>,           // Coroutine resumes the last generation took, if the engine counts them
+    step_size       : u64,                     // Generations per update
+    step_log2       : u8,
+    viewport        : Viewport,
+    universe        : Option<UniverseStats>,   // Set while an unbounded engine is selected
+    cycle           : Option<Cycle>,
+    analysis        : Option<Analysis>,
+    classifying     : bool,
+    census          : Option<(u64, Vec<CensusEntry>)>,
+    counting        : bool,
+    history         : Arc<ActivityHistory>,
+    ages            : Option<Arc<CellAges>>,
+    rotor           : Vec<(usize, usize)>,     // Display (row, col) of the classified oscillator's rotor cells in view
+}
+
+impl World {
+    fn new(width: usize, height: usize) -> Self {
+        let rule = Rule::default();
+        let boundary = Boundary::default();
+        let engine_kind = if width * height > CLOSURE_ENGINE_LIMIT { EngineKind::BitPacked } else { EngineKind::Closure };
+        let cell_schedule = CellSchedule::default();
         
         Self {
-            grid             : [[false; 52]; 52],
-            next_grid        : [[false; 52]; 52],
-            cell_functions,
+            grid             : TGrid::new(width, height),
+            engine           : engine_kind.create_grid_engine(width, height, rule, boundary, cell_schedule),
+            engine_kind,
+            cell_schedule,
+            universe         : None,
+            viewport         : Viewport::default(),
             is_running       : false,
-            last_update      : Instant::now(),
             update_interval  : Duration::from_millis(200),
             generation       : 0,
-            live_color       : Color32::from_rgb(0, 200, 0),
-            dead_color       : Color32::from_rgb(40, 40, 40),
+            step_log2        : 0,
             selected_pattern : 0,
-            grid_history     : [0; 10],  // Initialize array with zeros
-            history_count    : 0,        // Start counter at zero
+            rule,
+            boundary,
+            cycle_detector   : CycleDetector::default(),
+            cycle            : None,
+            analysis         : None,
+            classifying      : None,
+            census           : None,
+            counting         : None,
+            history          : Arc::default(),
+            ages             : None,
+            handle           : None,
         }
     }
-}
-
-impl GameOfLifeInterface for GameOfLife {
-    fn update_generation(&mut self) {
-        // Clear the next grid first
-        self.next_grid = [[false; 52]; 52];
+    
+    /// Changes the active area, keeping the cells that still fit
+    /// With an unbounded engine only the viewport changes size, the bounded engine is rebuilt on the way back
+    fn resize_grid(&mut self, width: usize, height: usize) {
+        self.grid.resize(width, height);
+        if self.universe.is_none() {
+            self.engine.resize(width, height);
+        }
+        self.reset_cycle();
+    }
+    
+    /// Swaps engines, carrying the cells across
+    /// Leaving an unbounded engine keeps only what is inside the viewport
+    fn set_engine(&mut self, engine_kind: EngineKind) {
+        if engine_kind == self.engine_kind { return; }
         
-        // Execute all 2500 cell functions, each writes directly to next_grid
-        self.cell_functions.iter().for_each(|cell_func| {
-            cell_func(&self.grid, &mut self.next_grid);
-        });
+        if let Some(universe) = &self.universe {
+            universe.copy_to_grid(&mut self.grid, self.viewport.top, self.viewport.left);
+        }
         
-        // Assert that border cells remain dead (error checking) - debug only
-        debug_assert!(self.check_border_cells_dead());
+        self.universe = match engine_kind {
+            EngineKind::Closure | EngineKind::Cells | EngineKind::BitPacked => None,
+            EngineKind::Sparse   => Some(Box::new(SparseLife::new(self.rule))),
+            EngineKind::HashLife => Some(Box::new(HashLife::new(self.rule))),
+        };
         
-        // Copy next generation to current
-        self.grid = self.next_grid;
-        self.generation += 1;
+        match &mut self.universe {
+            Some(universe) => {
+                universe.load_grid(&self.grid, self.viewport.top, self.viewport.left);
+                universe.set_step_log2(self.step_log2);
+            }
+            None => self.engine = engine_kind.create_grid_engine(self.grid.width(), self.grid.height(), self.rule, self.boundary, self.cell_schedule),
+        }
         
-        // Check for cycles and pause if detected
-        if self.check_for_cycle() { self.is_running = false; }
+        self.engine_kind = engine_kind;
+        self.reset_cycle();
     }
     
-    fn hash_grid(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        // Only hash the active area (1..51, 1..51), ignore borders
-        for row in 1..51 {
-            for col in 1..51 {
-                self.grid[row][col].hash(&mut hasher);
+    /// Regroups the cell coroutines or changes their scheduler, the cells themselves are untouched
+    fn set_cell_schedule(&mut self, cell_schedule: CellSchedule) {
+        self.cell_schedule = cell_schedule;
+        if self.engine_kind == EngineKind::Cells {
+            self.engine = self.engine_kind.create_grid_engine(self.grid.width(), self.grid.height(), self.rule, self.boundary, cell_schedule);
+        }
+    }
+    
+    fn apply_random_pattern(&mut self) {
+        patterns::apply_random_pattern(&mut self.grid, self.generation as u32);
+        if let Some(universe) = &mut self.universe {
+            universe.load_grid(&self.grid, self.viewport.top, self.viewport.left);
+        }
+        self.generation = 0;
+        self.history = Arc::default();
+        self.reset_cycle();
+    }
+    
+    /// Sets how many generations one update advances, as a power of two
+    fn set_step_log2(&mut self, step_log2: u8) {
+        self.step_log2 = step_log2;
+        if let Some(universe) = &mut self.universe {
+            universe.set_step_log2(step_log2);
+        }
+    }
+    
+    /// Generations advanced by each update with the current engine
+    fn step_size(&self) -> u64 {
+        self.universe.as_ref().map_or(1, |universe| 1 << universe.step_log2())
+    }
+    
+    /// Moves the viewport by whole cells, which stops it following the population
+    fn pan(&mut self, rows: i64, cols: i64) {
+        self.viewport.follow = false;
+        self.viewport.top += rows;
+        self.viewport.left += cols;
+        self.reset_ages();
+    }
+    
+    fn set_follow(&mut self, follow: bool) {
+        self.viewport.follow = follow;
+        self.follow_population();
+        self.reset_ages();
+    }
+    
+    /// Re-centres the viewport on the population when following it
+    fn follow_population(&mut self) {
+        if !self.viewport.follow { return; }
+        
+        if let Some(bounds) = self.universe.as_ref().and_then(|universe| universe.bounding_box()) {
+            let (center_row, center_col) = bounds.center();
+            self.viewport.top = center_row - (self.grid.height() / 2) as i64;
+            self.viewport.left = center_col - (self.grid.width() / 2) as i64;
+        }
+    }
+    
+    /// Forgets the generations seen so far, what they were classified as and counted, after anything that changes the cells other than a step
+    fn reset_cycle(&mut self) {
+        self.cycle_detector.reset();
+        self.cycle = None;
+        // The cells as they are now are the first generation the bounded detector sees, universes observe their own
+        if self.universe.is_none() {
+            self.check_for_cycle();
+        }
+        self.stop_classifying();
+        self.stop_counting();
+        self.reset_ages();
+    }
+    
+    /// Starts or stops tracking cell ages, for the renderings that shade cells by them
+    fn set_track_ages(&mut self, track: bool) {
+        self.ages = track.then(|| Arc::new(CellAges::new(&self.display_grid())));
+    }
+    
+    /// Starts the ages over from the cells shown now, if they are tracked
+    fn reset_ages(&mut self) {
+        if self.ages.is_some() {
+            self.set_track_ages(true);
+        }
+    }
+    
+    /// Ages the displayed cells by the update just made, starting over if the viewport moved since `origin`
+    /// A HashLife stride of several generations ages them by one
+    fn record_ages(&mut self, origin: (i64, i64)) {
+        if self.ages.is_none() { return; }
+        if self.display_origin() != origin {
+            self.reset_ages();
+            return;
+        }
+        
+        let window = self.universe.is_some().then(|| self.display_grid());
+        if let Some(ages) = &mut self.ages {
+            Arc::make_mut(ages).update(window.as_ref().unwrap_or(&self.grid));
+        }
+    }
+    
+    /// The active area, or the viewport's window onto the universe
+    fn display_grid(&self) -> TGrid {
+        match &self.universe {
+            Some(universe) => {
+                let mut window = TGrid::new(self.grid.width(), self.grid.height());
+                universe.copy_to_grid(&mut window, self.viewport.top, self.viewport.left);
+                window
+            }
+            None => self.grid.clone(),
+        }
+    }
+    
+    /// Live cells in universe coordinates, grid cells keep their grid (row, col)
+    /// None when there are too many to classify
+    fn live_cells(&self) -> Option<Vec<Cell>> {
+        match &self.universe {
+            Some(universe) => {
+                if universe.population() > classify::MAX_POPULATION { return None; }
+                Some(universe.collect_live_cells())
+            }
+            None => {
+                if self.grid.count_live_cells() as u64 > classify::MAX_POPULATION { return None; }
+                Some(classify::grid_cells(&self.grid))
             }
         }
-        hasher.finish()
     }
     
-    fn check_for_cycle(&mut self) -> bool {
-        let current_hash = self.hash_grid();
-        if self.grid_history.contains(&current_hash) { return true; } // Cycle detected
+    /// Runs the current cells forward on an unbounded plane to name what they are
+    /// The run takes a thread of its own so generations and edits carry on meanwhile, the analysis arrives as a command
+    fn classify(&mut self) {
+        self.stop_classifying();
+        let (Some(cells), Some(handle)) = (self.live_cells(), &self.handle) else { return; };
         
-        self.grid_history[self.history_count % 10] = current_hash; // Circular buffer
-        self.history_count += 1;
+        let cancel = CancelToken::default();
+        let rule = self.rule;
+        handle.spawn_work(
+            cancel.clone(),
+            move |cancel| classify::classify_cancellable(cells, rule, classify::MAX_GENERATIONS, cancel),
+            |world, analysis| {
+                world.analysis = Some(analysis);
+                world.classifying = None;
+            },
+        );
+        self.classifying = Some(cancel);
+    }
+    
+    /// Forgets the analysis and abandons one still running
+    fn stop_classifying(&mut self) {
+        if let Some(cancel) = self.classifying.take() {
+            cancel.cancel();
+        }
+        self.analysis = None;
+    }
+    
+    /// Splits the current cells into objects and counts them by name
+    /// Counted on a thread of its own like a classification, the entries arrive as a command
+    fn take_census(&mut self) {
+        self.stop_counting();
+        let (Some(cells), Some(handle)) = (self.live_cells(), &self.handle) else { return; };
         
-        false // No cycle
+        let cancel = CancelToken::default();
+        let (rule, generation) = (self.rule, self.generation);
+        handle.spawn_work(
+            cancel.clone(),
+            move |cancel| census::census_cancellable(cells, rule, cancel),
+            move |world, entries| {
+                world.census = Some((generation, entries));
+                world.counting = None;
+            },
+        );
+        self.counting = Some(cancel);
+    }
+    
+    /// Forgets the census and abandons one still running
+    fn stop_counting(&mut self) {
+        if let Some(cancel) = self.counting.take() {
+            cancel.cancel();
+        }
+        self.census = None;
+    }
+    
+    /// Adds the generation just computed to the history, births and deaths only when the grid from before it was kept
+    fn record_activity(&mut self, before: Option<TGrid>) {
+        let activity = match before {
+            Some(before) => Activity::between(self.generation, &before, &self.grid),
+            None => Activity {
+                generation : self.generation,
+                population : self.universe.as_ref().map_or(0, |universe| universe.population()),
+                ..Activity::default()
+            },
+        };
+        Arc::make_mut(&mut self.history).record(activity);
+    }
+    
+    /// Universe coordinates of the top-left displayed cell
+    fn display_origin(&self) -> (i64, i64) {
+        match &self.universe {
+            Some(_) => (self.viewport.top, self.viewport.left),
+            None => (GRID_START as i64, GRID_START as i64),
+        }
+    }
+}
+
+impl GameOfLifeInterface for World {
+    fn update_generation(&mut self) {
+        // Bounded grids are compared with the generation before to count births and deaths
+        let before = self.universe.is_none().then(|| self.grid.clone());
+        let origin = self.display_origin();
+        match &mut self.universe {
+            Some(universe) => {
+                universe.update_generation();
+                // A universe that can go no further is left as it was, so the run stops there
+                if universe.limit().is_some() {
+                    self.is_running = false;
+                    return;
+                }
+            }
+            // Execute all cell functions and swap next generation into current
+            None => self.engine.step(&mut self.grid),
+        }
+        self.generation += self.step_size();
+        self.follow_population();
+        self.record_activity(before);
+        self.record_ages(origin);
+        
+        // Check for cycles, pause, classify the pattern and count what it has settled into when one is first entered
+        let was_cycling = self.cycle.is_some();
+        self.cycle = self.check_for_cycle();
+        if self.cycle.is_some() {
+            self.is_running = false;
+            if !was_cycling {
+                self.classify();
+                self.take_census();
+            }
+        }
+    }
+    
+    fn hash_grid(&self) -> u64 {
+        match &self.universe {
+            Some(universe) => universe.hash_grid(),
+            None => cycle::hash_grid(&self.grid),
+        }
+    }
+    
+    fn check_for_cycle(&mut self) -> Option<Cycle> {
+        match &mut self.universe {
+            // A universe counts from when it was loaded, which can be later than generation 0 here
+            Some(universe) => {
+                let offset = self.generation.saturating_sub(universe.generation());
+                universe.check_for_cycle().map(|cycle| cycle.shifted(offset))
+            }
+            None => {
+                let current_hash = cycle::hash_grid(&self.grid);
+                self.cycle_detector.observe(self.generation, current_hash, self.grid.count_live_cells() as u64, &self.grid)
+            }
+        }
     }
     
     fn clear_grid(&mut self) {
-        self.grid = [[false; 52]; 52];
+        self.grid.clear();
+        if let Some(universe) = &mut self.universe {
+            universe.clear_grid();
+        }
         self.generation = 0;
-        self.grid_history = [0; 10];  // Reset array to zeros
-        self.history_count = 0;       // Reset counter
+        self.history = Arc::default();
+        self.reset_cycle();
     }
     
     fn apply_selected_pattern(&mut self) {
         if let Some(pattern) = patterns::PATTERNS.get(self.selected_pattern) {
-            patterns::apply_pattern(&mut self.grid, pattern);
+            // Unbounded engines take the whole pattern, the grid centres it and only clips what cannot fit
+            match &mut self.universe {
+                Some(universe) => universe.apply_pattern(pattern),
+                None => patterns::apply_pattern(&mut self.grid, pattern),
+            }
             self.generation = 0;
-            self.grid_history = [0; 10];  // Reset array to zeros
-            self.history_count = 0;       // Reset counter
+            self.history = Arc::default();
+            self.reset_cycle();
+        }
+    }
+    
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.engine.set_rule(rule);
+        if let Some(universe) = &mut self.universe {
+            universe.set_rule(rule);
         }
+        self.reset_cycle();
+    }
+    
+    fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.engine.set_boundary(boundary);
+        self.reset_cycle();
     }
     
     fn check_border_cells_dead(&self) -> bool {
-        for i in 0..52 {
-            if self.next_grid[0][i]  != false { panic!("Top border cell [0, {}] should be false", i); }
-            if self.next_grid[51][i] != false { panic!("Bottom border cell [51, {}] should be false", i); }
-            if self.next_grid[i][0]  != false { panic!("Left border cell [{}, 0] should be false", i); }
-            if self.next_grid[i][51] != false { panic!("Right border cell [{}, 51] should be false", i); }
+        match &self.universe {
+            Some(universe) => universe.check_border_cells_dead(),
+            None => self.grid.check_border_cells_dead(),
+        }
+    }
+}
+
+impl Simulation for World {
+    type Snapshot = Snapshot;
+    
+    fn snapshot(&self) -> Snapshot {
+        let grid = self.display_grid();
+        
+        // Rotor cells as display positions, dropping any outside the view
+        let (top, left) = self.display_origin();
+        let rotor = match &self.analysis {
+            Some(Analysis { classification: Classification::Oscillator { rotor, .. }, .. }) => rotor.iter()
+                .filter_map(|&(row, col)| Some((usize::try_from(row - top).ok()?, usize::try_from(col - left).ok()?)))
+                .filter(|&(row, col)| row < grid.height() && col < grid.width())
+                .collect(),
+            _ => Vec::new(),
+        };
+        
+        Snapshot {
+            grid,
+            generation      : self.generation,
+            is_running      : self.is_running,
+            update_interval : self.update_interval,
+            rule            : self.rule,
+            boundary        : self.boundary,
+            engine_kind     : self.engine_kind,
+            cell_schedule   : self.cell_schedule,
+            last_slices     : self.engine.last_slices().filter(|_| self.universe.is_none()),
+            step_size       : self.step_size(),
+            step_log2       : self.step_log2,
+            viewport        : self.viewport,
+            universe        : self.universe.as_ref().map(|universe| UniverseStats {
+                population : universe.population(),
+                bounds     : universe.bounding_box(),
+                limit      : universe.limit(),
+            }),
+            cycle           : self.cycle,
+            analysis        : self.analysis.clone(),
+            classifying     : self.classifying.is_some(),
+            census          : self.census.clone(),
+            counting        : self.counting.is_some(),
+            history         : self.history.clone(),
+            ages            : self.ages.clone(),
+            rotor,
         }
-        true
     }
-}
\ No newline at end of file
+    
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    
+    fn set_running(&mut self, running: bool) {
+        self.is_running = running;
+    }
+    
+    fn update_interval(&self) -> Duration {
+        self.update_interval
+    }
+    
+    /// Flips the cell shown at a display position, whichever engine is running
+    fn toggle_cell(&mut self, display_row: usize, display_col: usize) {
+        match &mut self.universe {
+            Some(universe) => universe.toggle_cell(self.viewport.top + display_row as i64, self.viewport.left + display_col as i64),
+            None => self.grid.toggle(display_row + GRID_START, display_col + GRID_START),
+        }
+        self.reset_cycle();
+    }
+    
+    fn load_pattern(&mut self, index: usize) {
+        self.selected_pattern = index;
+        self.apply_selected_pattern();
+    }
+    
+    fn attach(&mut self, handle: SimulationHandle<World>) {
+        self.handle = Some(handle);
+    }
+}
+
+/// Conway's Game of Life window
+/// The simulation runs on its own thread, the UI draws its latest snapshot and sends commands back
+struct GameOfLife {
+    simulation       : SimulationThread<World>,
+    live_color       : Color32,
+    dead_color       : Color32,
+    rotor_color      : Color32,            // Highlights the rotor of a classified oscillator
+    render           : RenderStyle,        // Shading by cell age, recent activity or trails, with its colour ramp
+    selected_pattern : usize,              // Pattern chosen in the dropdown, loaded by Apply Pattern
+    rule_text        : String,             // Rulestring being edited in the UI
+    rule_error       : Option<String>,     // Why the last typed rulestring was rejected
+    new_width        : usize,              // Pending size from the resize controls
+    new_height       : usize,
+    drag             : egui::Vec2,         // Drag distance not yet turned into whole cells
+    grid_texture     : Option<egui::TextureHandle>,  // Large grids are drawn as one texel per cell
+    show_history     : bool,               // Population and activity chart under the grid
+    export_status    : Option<String>,     // Where the history was last exported to, or why it failed
+}
+
+impl GameOfLife {
+    fn new(world: World, ctx: &egui::Context) -> Self {
+        let (width, height) = (world.grid.width(), world.grid.height());
+        let rule_text = world.rule.to_string();
+        
+        // Each published snapshot wakes the UI, so nothing polls while the simulation is idle
+        let ctx = ctx.clone();
+        Self {
+            simulation       : SimulationThread::spawn(world, move || ctx.request_repaint()),
+            live_color       : Color32::from_rgb(0, 200, 0),
+            dead_color       : Color32::from_rgb(40, 40, 40),
+            rotor_color      : Color32::from_rgb(220, 60, 200),
+            render           : RenderStyle::default(),
+            selected_pattern : 0,
+            rule_text,
+            rule_error       : None,
+            new_width        : width,
+            new_height       : height,
+            drag             : egui::Vec2::ZERO,
+            grid_texture     : None,
+            show_history     : true,
+            export_status    : None,
+        }
+    }
+    
+    /// Switches rule on the simulation thread and shows it in the rulestring box
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule_text = rule.to_string();
+        self.rule_error = None;
+        self.simulation.apply(move |world| world.set_rule(rule));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn blinker_cycle_starts_at_the_loaded_generation() {
+        let mut world = World::new(10, 10);
+        let blinker = patterns::PATTERNS.iter().position(|pattern| pattern.name == "Blinker").unwrap();
+        world.load_pattern(blinker);
+        
+        // Running stops once the cycle is confirmed, a few generations in
+        world.set_running(true);
+        while world.is_running && world.generation < 10 {
+            world.update_generation();
+        }
+        let cycle = world.cycle.expect("a blinker repeats after two generations");
+        assert_eq!((cycle.period, cycle.start), (2, 0));
+    }
+}
@@ -1,15 +1,146 @@
 use eframe::egui;
-use egui::{Color32, Rect, Stroke, Vec2};
-use std::time::{Duration, Instant};
-use crate::{GameOfLife, patterns, GameOfLifeInterface};
+use egui::{Color32, ColorImage, Rect, Stroke, TextureOptions, Vec2};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
+use life_core::{patterns, Command, GameOfLifeInterface, TGrid, BOUNDARIES, GRID_START, RULES};
+use life_core::age::{CellAge, CellAges, MAX_HEAT_WINDOW};
+use life_core::cell_engine::GRANULARITIES;
+use life_core::classify;
+use life_core::hashlife_engine::MAX_STEP_LOG2;
+use life_core::history::{self, ActivityHistory};
+use life_core::palette::{RenderMode, RAMPS, RENDER_MODES};
+use life_core::scheduler::SCHEDULING_POLICIES;
+use crate::{EngineKind, GameOfLife};
+
+/// Cells smaller than this many pixels are drawn as a texture instead of one rectangle each
+const MIN_BOX_SIZE: f32 = 3.0;
+
+/// Height of the population and activity chart under the grid
+const HISTORY_HEIGHT: f32 = 100.0;
+
+/// Most recent generations on the chart, older ones scroll off to the left
+const HISTORY_WINDOW: usize = 500;
+
+/// File the history is exported to, in the working directory
+const HISTORY_CSV: &str = "life_history.csv";
+
+/// Colours of the population, births, deaths and changed-cell lines, in the order of `history::SERIES`
+const HISTORY_COLORS: [Color32; 4] = [
+    Color32::from_rgb(0, 200, 0),
+    Color32::from_rgb(80, 160, 255),
+    Color32::from_rgb(230, 80, 80),
+    Color32::from_rgb(230, 200, 60),
+];
+
+impl GameOfLife {
+    /// One pixel per displayed cell
+    fn grid_image(&self, grid: &TGrid, rotor: &HashSet<(usize, usize)>, ages: Option<&CellAges>) -> ColorImage {
+        let (width, height) = (grid.width(), grid.height());
+        let mut image = ColorImage::new([width, height], self.dead_color);
+        for display_row in 0..height {
+            for display_col in 0..width {
+                let alive = grid[display_row + GRID_START][display_col + GRID_START];
+                let age = ages.map(|ages| ages.get(display_row, display_col));
+                image.pixels[display_row * width + display_col] = self.cell_color(alive, rotor.contains(&(display_row, display_col)), age);
+            }
+        }
+        image
+    }
+    
+    /// Scrolling line chart of the recent population and activity, under the latest values and the CSV export
+    /// Unbounded engines only record the population
+    fn history_chart(&mut self, ui: &mut egui::Ui, history: &ActivityHistory) {
+        let samples = history.samples();
+        let recent = samples.range(samples.len().saturating_sub(HISTORY_WINDOW)..);
+        
+        ui.horizontal(|ui| {
+            for ((name, series), color) in history::SERIES.into_iter().zip(HISTORY_COLORS) {
+                let latest = samples.back().and_then(series).map_or("-".to_string(), |count| count.to_string());
+                ui.colored_label(color, format!("{}: {}", name, latest));
+            }
+            
+            ui.separator();
+            
+            let hover = format!("Writes every recorded generation, up to the last {}", history::HISTORY_LENGTH);
+            if ui.button("💾 Export CSV").on_hover_text(hover).clicked() {
+                let written = File::create(HISTORY_CSV).and_then(|file| history.write_csv(BufWriter::new(file)));
+                self.export_status = Some(match written {
+                    Ok(()) => format!("Saved {} generations to {}", samples.len(), HISTORY_CSV),
+                    Err(error) => format!("Export failed: {}", error),
+                });
+            }
+            if let Some(status) = &self.export_status {
+                ui.label(status);
+            }
+        });
+        
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), HISTORY_HEIGHT), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+        
+        // One scale for every series, the largest count in view reaches the top
+        let peak = recent.clone()
+            .flat_map(|activity| history::SERIES.map(|(_, series)| series(activity).unwrap_or(0)))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let x = |index: usize| rect.left() + rect.width() * index as f32 / (HISTORY_WINDOW - 1) as f32;
+        let y = |count: u64| rect.bottom() - rect.height() * count as f32 / peak as f32;
+        for ((_, series), color) in history::SERIES.into_iter().zip(HISTORY_COLORS) {
+            let points: Vec<egui::Pos2> = recent.clone()
+                .enumerate()
+                .filter_map(|(index, activity)| series(activity).map(|count| egui::pos2(x(index), y(count))))
+                .collect();
+            painter.add(egui::Shape::line(points, Stroke::new(1.5, color)));
+        }
+        painter.text(rect.left_top() + Vec2::splat(4.0), egui::Align2::LEFT_TOP, peak.to_string(), egui::FontId::monospace(10.0), Color32::GRAY);
+    }
+    
+    /// Rotor cells stand out from the rest, dimmed in the phases they are dead
+    /// Other cells are shaded by the rendering mode where the simulation tracks their age
+    fn cell_color(&self, alive: bool, in_rotor: bool, age: Option<CellAge>) -> Color32 {
+        let dead = [self.dead_color.r(), self.dead_color.g(), self.dead_color.b()];
+        match (alive, in_rotor) {
+            (true, true)  => self.rotor_color,
+            (false, true) => self.rotor_color.linear_multiply(0.3),
+            _ => match age.and_then(|age| self.render.shade(age, dead)) {
+                Some([r, g, b]) => Color32::from_rgb(r, g, b),
+                None if alive   => self.live_color,
+                None            => self.dead_color,
+            },
+        }
+    }
+}
 
 impl eframe::App for GameOfLife {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Auto-update if running
-        if self.is_running && self.last_update.elapsed() >= self.update_interval {
-            self.update_generation();
-            self.last_update = Instant::now();
-            ctx.request_repaint(); // Ensure continuous updates
+        // Everything below draws this one snapshot, changes go back to the simulation thread as commands
+        let snapshot = self.simulation.snapshot();
+        
+        // Objects counted by name, from the last census
+        if let Some((generation, entries)) = &snapshot.census {
+            egui::SidePanel::right("census").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(format!("Census at generation {}", generation));
+                    if ui.small_button("✖").clicked() {
+                        self.simulation.apply(|world| world.census = None);
+                    }
+                });
+                ui.label(format!("{} objects", entries.iter().map(|entry| entry.count).sum::<usize>()));
+                ui.separator();
+                
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("census_grid").striped(true).show(ui, |ui| {
+                        for entry in entries {
+                            ui.label(&entry.name);
+                            ui.label(entry.count.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
         }
         
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -17,23 +148,23 @@ impl eframe::App for GameOfLife {
             
             // Controls
             ui.horizontal(|ui| {
-                let button_text = if self.is_running { "⏸ Pause" } else { "▶ Start" };
+                let button_text = if snapshot.is_running { "⏸ Pause" } else { "▶ Start" };
                 if ui.button(button_text).clicked() {
-                    self.is_running = !self.is_running;
-                    if self.is_running {
-                        self.last_update = Instant::now();
-                    }
+                    self.simulation.send(if snapshot.is_running { Command::Pause } else { Command::Start });
+                }
+                
+                if ui.button("⏭ Step").clicked() {
+                    self.simulation.send(Command::Step);
                 }
                 
                 if ui.button("⏹ Clear").clicked() {
-                    self.is_running = false;
-                    self.clear_grid();
+                    self.simulation.send(Command::Pause);
+                    self.simulation.apply(|world| world.clear_grid());
                 }
                 
                 if ui.button("🎲 Random").clicked() {
-                    self.is_running = false;
-                    patterns::apply_random_pattern(&mut self.grid, self.generation);
-                    self.generation = 0;
+                    self.simulation.send(Command::Pause);
+                    self.simulation.apply(|world| world.apply_random_pattern());
                 }
                 
                 ui.separator();
@@ -49,13 +180,45 @@ impl eframe::App for GameOfLife {
                     });
                 
                 if ui.button("Apply Pattern").clicked() {
-                    self.is_running = false;
-                    self.apply_selected_pattern();
+                    self.simulation.send(Command::Pause);
+                    self.simulation.send(Command::LoadPattern(self.selected_pattern));
                 }
                 
                 ui.separator();
                 
-                ui.label(format!("Generation: {}", self.generation));
+                // Rule dropdown, or type any B/S rulestring and press Enter
+                ui.label("Rule:");
+                let rule_name = RULES.iter()
+                    .find(|named| named.rule == snapshot.rule)
+                    .map_or("Custom", |named| named.name);
+                egui::ComboBox::from_id_source("rule_selector")
+                    .selected_text(rule_name)
+                    .show_ui(ui, |ui| {
+                        for named in RULES {
+                            let label = format!("{} ({})", named.name, named.rule);
+                            if ui.selectable_label(snapshot.rule == named.rule, label).clicked() {
+                                self.set_rule(named.rule);
+                            }
+                        }
+                    });
+                
+                let rule_edit = ui.add(egui::TextEdit::singleline(&mut self.rule_text).desired_width(90.0));
+                if rule_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    match self.rule_text.parse() {
+                        Ok(rule) => self.set_rule(rule),
+                        Err(error) => self.rule_error = Some(error.to_string()),
+                    }
+                }
+                if let Some(error) = &self.rule_error {
+                    ui.colored_label(Color32::RED, "⚠").on_hover_text(error);
+                }
+                
+                ui.separator();
+                
+                ui.label(format!("Generation: {}", snapshot.generation));
+                if let Some(cycle) = snapshot.cycle {
+                    ui.colored_label(Color32::from_rgb(230, 140, 0), cycle.to_string());
+                }
             });
             
             ui.separator();
@@ -63,9 +226,10 @@ impl eframe::App for GameOfLife {
             // Speed control
             ui.horizontal(|ui| {
                 ui.label("Speed:");
-                let mut speed = 1000.0 / self.update_interval.as_millis() as f32;
+                let mut speed = 1000.0 / snapshot.update_interval.as_millis() as f32;
                 if ui.add(egui::Slider::new(&mut speed, 0.5..=90.0).suffix(" gen/sec")).changed() {
-                    self.update_interval = Duration::from_millis((1000.0 / speed) as u64);
+                    let update_interval = Duration::from_millis((1000.0 / speed) as u64);
+                    self.simulation.apply(move |world| world.update_interval = update_interval);
                 }
                 
                 ui.separator();
@@ -75,6 +239,162 @@ impl eframe::App for GameOfLife {
                 ui.color_edit_button_srgba(&mut self.live_color);
                 ui.label("Dead:");
                 ui.color_edit_button_srgba(&mut self.dead_color);
+                ui.label("Rotor:");
+                ui.color_edit_button_srgba(&mut self.rotor_color);
+                
+                ui.separator();
+                
+                // Grid size, applied live and keeping the cells that still fit
+                ui.label("Grid:");
+                ui.add(egui::DragValue::new(&mut self.new_width).clamp_range(1..=4096));
+                ui.label("x");
+                ui.add(egui::DragValue::new(&mut self.new_height).clamp_range(1..=4096));
+                if ui.button("Resize").clicked() {
+                    let (width, height) = (self.new_width, self.new_height);
+                    self.simulation.apply(move |world| world.resize_grid(width, height));
+                }
+                
+                ui.separator();
+                
+                // What happens at the edges of the grid, unbounded engines have none
+                ui.add_enabled_ui(snapshot.universe.is_none(), |ui| {
+                    ui.label("Edges:");
+                    egui::ComboBox::from_id_source("boundary_selector")
+                        .selected_text(snapshot.boundary.name())
+                        .show_ui(ui, |ui| {
+                            for &boundary in BOUNDARIES {
+                                if ui.selectable_label(snapshot.boundary == boundary, boundary.name()).clicked() {
+                                    self.simulation.apply(move |world| world.set_boundary(boundary));
+                                }
+                            }
+                        });
+                });
+            });
+            
+            ui.separator();
+            
+            // Cell shading by how long cells have lived, how often they changed lately or where they just died
+            ui.horizontal(|ui| {
+                ui.label("Render:");
+                let mut mode = self.render.mode;
+                egui::ComboBox::from_id_source("render_selector")
+                    .selected_text(mode.name())
+                    .show_ui(ui, |ui| {
+                        for &choice in RENDER_MODES {
+                            ui.selectable_value(&mut mode, choice, choice.name());
+                        }
+                    });
+                if mode.uses_ages() != self.render.mode.uses_ages() {
+                    let track = mode.uses_ages();
+                    self.simulation.apply(move |world| world.set_track_ages(track));
+                }
+                self.render.mode = mode;
+                
+                match mode {
+                    RenderMode::Plain => {}
+                    RenderMode::Age => {
+                        ui.label("Span:");
+                        ui.add(egui::DragValue::new(&mut self.render.age_span).clamp_range(1..=10_000).suffix(" gen"))
+                            .on_hover_text("Generations alive that reach the top of the ramp");
+                    }
+                    RenderMode::Heat => {
+                        ui.label("Window:");
+                        ui.add(egui::DragValue::new(&mut self.render.heat_window).clamp_range(1..=MAX_HEAT_WINDOW).suffix(" gen"))
+                            .on_hover_text("Generations each cell's changes are counted over");
+                    }
+                    RenderMode::Trails => {
+                        ui.label("Fade:");
+                        ui.add(egui::DragValue::new(&mut self.render.trail_length).clamp_range(1..=1000).suffix(" gen"))
+                            .on_hover_text("Generations a dead cell's trail takes to fade out");
+                    }
+                }
+                
+                // Preset ramps, then each of their colours from low to high
+                if mode.uses_ages() {
+                    ui.separator();
+                    ui.label("Ramp:");
+                    let ramp_name = RAMPS.iter()
+                        .find(|named| named.ramp == self.render.ramp)
+                        .map_or("Custom", |named| named.name);
+                    egui::ComboBox::from_id_source("ramp_selector")
+                        .selected_text(ramp_name)
+                        .show_ui(ui, |ui| {
+                            for named in RAMPS {
+                                if ui.selectable_label(self.render.ramp == named.ramp, named.name).clicked() {
+                                    self.render.ramp = named.ramp;
+                                }
+                            }
+                        });
+                    ui.color_edit_button_srgb(&mut self.render.ramp.low);
+                    ui.color_edit_button_srgb(&mut self.render.ramp.middle);
+                    ui.color_edit_button_srgb(&mut self.render.ramp.high);
+                }
+            });
+            
+            ui.separator();
+            
+            // Engine selection and viewport controls
+            ui.horizontal(|ui| {
+                ui.label("Engine:");
+                let mut engine_kind = snapshot.engine_kind;
+                egui::ComboBox::from_id_source("engine_selector")
+                    .selected_text(engine_kind.name())
+                    .show_ui(ui, |ui| {
+                        for kind in EngineKind::ALL {
+                            ui.selectable_value(&mut engine_kind, kind, kind.name());
+                        }
+                    });
+                if engine_kind != snapshot.engine_kind {
+                    self.simulation.apply(move |world| world.set_engine(engine_kind));
+                }
+                
+                // Cell coroutines yield after every neighbour, grouped into tasks the scheduler interleaves
+                if snapshot.engine_kind == EngineKind::Cells {
+                    ui.separator();
+                    let mut cell_schedule = snapshot.cell_schedule;
+                    ui.label("Tasks:");
+                    egui::ComboBox::from_id_source("granularity_selector")
+                        .selected_text(cell_schedule.granularity.name())
+                        .show_ui(ui, |ui| {
+                            for &granularity in GRANULARITIES {
+                                ui.selectable_value(&mut cell_schedule.granularity, granularity, granularity.name());
+                            }
+                        });
+                    ui.label("Scheduler:");
+                    egui::ComboBox::from_id_source("cell_scheduler_selector")
+                        .selected_text(cell_schedule.policy.name())
+                        .show_ui(ui, |ui| {
+                            for &policy in SCHEDULING_POLICIES {
+                                ui.selectable_value(&mut cell_schedule.policy, policy, policy.name());
+                            }
+                        });
+                    if cell_schedule != snapshot.cell_schedule {
+                        self.simulation.apply(move |world| world.set_cell_schedule(cell_schedule));
+                    }
+                    if let Some(resumes) = snapshot.last_slices {
+                        ui.label(format!("{} resumes per generation", resumes));
+                    }
+                }
+                
+                // HashLife can jump 2^k generations per update, Start/Pause then runs at that stride
+                if snapshot.engine_kind == EngineKind::HashLife {
+                    ui.separator();
+                    ui.label("Step: 2^");
+                    let mut step_log2 = snapshot.step_log2;
+                    if ui.add(egui::DragValue::new(&mut step_log2).clamp_range(0..=MAX_STEP_LOG2)).changed() {
+                        self.simulation.apply(move |world| world.set_step_log2(step_log2));
+                    }
+                    ui.label(format!("= {} generations", snapshot.step_size));
+                }
+                
+                if snapshot.universe.is_some() {
+                    ui.separator();
+                    let mut follow = snapshot.viewport.follow;
+                    if ui.checkbox(&mut follow, "Follow population").changed() {
+                        self.simulation.apply(move |world| world.set_follow(follow));
+                    }
+                    ui.label(format!("Viewport at ({}, {}), drag to pan", snapshot.viewport.top, snapshot.viewport.left));
+                }
             });
             
             ui.separator();
@@ -84,15 +404,35 @@ impl eframe::App for GameOfLife {
             
             ui.separator();
             
-            // Draw the grid (only show the active 50x50 area)
-            let box_size = 15.0;
-            let spacing = 0.5;
-            let grid_size = 50;  // Display size stays 50x50
+            // Draw the grid (only show the active area), shrinking cells to fit large grids
+            let grid = &snapshot.grid;
+            let rotor: HashSet<(usize, usize)> = snapshot.rotor.iter().copied().collect();
+            let (grid_width, grid_height) = (grid.width(), grid.height());
+            let chart_height = if self.show_history { HISTORY_HEIGHT + 30.0 } else { 0.0 };
+            let fit = (ui.available_width() / grid_width as f32)
+                .min((ui.available_height() - 40.0 - chart_height) / grid_height as f32);
+            let as_texture = fit < MIN_BOX_SIZE;
+            let spacing = if as_texture { 0.0 } else { 0.5 };
+            let box_size = if as_texture { fit.max(0.1) } else { (fit - spacing).clamp(1.0, 15.0) };
             
             let start_pos = ui.cursor().min;
-            let total_size = Vec2::splat((box_size + spacing) * grid_size as f32 - spacing);
+            let total_size = Vec2::new(
+                (box_size + spacing) * grid_width as f32 - spacing,
+                (box_size + spacing) * grid_height as f32 - spacing,
+            );
+            
+            let (response, painter) = ui.allocate_painter(total_size, egui::Sense::click_and_drag());
             
-            let (response, painter) = ui.allocate_painter(total_size, egui::Sense::click());
+            // Dragging pans an unbounded universe a whole cell at a time
+            if snapshot.universe.is_some() && response.dragged() {
+                self.drag += response.drag_delta();
+                let cell_step = box_size + spacing;
+                let (cols, rows) = ((self.drag.x / cell_step).trunc(), (self.drag.y / cell_step).trunc());
+                if cols != 0.0 || rows != 0.0 {
+                    self.simulation.apply(move |world| world.pan(-rows as i64, -cols as i64));
+                    self.drag -= Vec2::new(cols, rows) * cell_step;
+                }
+            }
             
             // Fill background
             painter.rect_filled(
@@ -101,38 +441,58 @@ impl eframe::App for GameOfLife {
                 Color32::BLACK,
             );
             
-            // Draw only the active area (grid[1..51][1..51])
-            for display_row in 0..grid_size {
-                for display_col in 0..grid_size {
-                    let grid_row = display_row + 1;  // Map to grid[1..51]
-                    let grid_col = display_col + 1;  // Map to grid[1..51]
-                    
-                    let x = start_pos.x + display_col as f32 * (box_size + spacing);
-                    let y = start_pos.y + display_row as f32 * (box_size + spacing);
-                    
-                    let rect = Rect::from_min_size(
-                        egui::pos2(x, y),
-                        Vec2::splat(box_size),
-                    );
-                    
-                    // Choose color based on cell state
-                    let cell_color = if self.grid[grid_row][grid_col] {
-                        self.live_color
-                    } else {
-                        self.dead_color
-                    };
-                    
-                    painter.rect_filled(rect, 1.0, cell_color);
-                    
-                    // Draw subtle border
-                    painter.rect_stroke(rect, 1.0, Stroke::new(0.2, Color32::from_gray(60)));
-                    
-                    // Handle clicking (only when not running)
-                    if !self.is_running && response.clicked() {
-                        if let Some(pos) = response.interact_pointer_pos() {
-                            if rect.contains(pos) {
-                                self.grid[grid_row][grid_col] = !self.grid[grid_row][grid_col];
-                            }
+            // Clicks toggle a cell (only when not running)
+            let clicked = (!snapshot.is_running && response.clicked())
+                .then(|| response.interact_pointer_pos())
+                .flatten();
+            
+            // Large grids go up as one texture, sampled smoothly once cells are below a pixel
+            if as_texture {
+                let image = self.grid_image(grid, &rotor, snapshot.ages.as_deref());
+                let options = if box_size < 1.0 { TextureOptions::LINEAR } else { TextureOptions::NEAREST };
+                let texture = match &mut self.grid_texture {
+                    Some(texture) => { texture.set(image, options); texture }
+                    None => self.grid_texture.insert(ui.ctx().load_texture("grid", image, options)),
+                };
+                painter.image(
+                    texture.id(),
+                    Rect::from_min_size(start_pos, total_size),
+                    Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+                
+                if let Some(pos) = clicked {
+                    let display_col = ((pos.x - start_pos.x) / box_size) as usize;
+                    let display_row = ((pos.y - start_pos.y) / box_size) as usize;
+                    if display_row < grid_height && display_col < grid_width {
+                        self.simulation.send(Command::ToggleCell { row: display_row, col: display_col });
+                    }
+                }
+            } else {
+                // Draw only the active area, or the viewport's window onto the universe
+                for display_row in 0..grid_height {
+                    for display_col in 0..grid_width {
+                        let x = start_pos.x + display_col as f32 * (box_size + spacing);
+                        let y = start_pos.y + display_row as f32 * (box_size + spacing);
+                        
+                        let rect = Rect::from_min_size(
+                            egui::pos2(x, y),
+                            Vec2::splat(box_size),
+                        );
+                        
+                        // Choose color based on cell state
+                        let alive = grid[display_row + GRID_START][display_col + GRID_START];
+                        let age = snapshot.ages.as_ref().map(|ages| ages.get(display_row, display_col));
+                        let cell_color = self.cell_color(alive, rotor.contains(&(display_row, display_col)), age);
+                        
+                        painter.rect_filled(rect, 1.0, cell_color);
+                        
+                        // Draw subtle border
+                        painter.rect_stroke(rect, 1.0, Stroke::new(0.2, Color32::from_gray(60)));
+                        
+                        if let Some(pos) = clicked
+                            && rect.contains(pos) {
+                            self.simulation.send(Command::ToggleCell { row: display_row, col: display_col });
                         }
                     }
                 }
@@ -140,21 +500,67 @@ impl eframe::App for GameOfLife {
             
             ui.separator();
             
-            // Statistics (count only the active area)
-            let live_cells: usize = (1..51).map(|row| 
-                (1..51).filter(|&col| self.grid[row][col]).count()
-            ).sum();
-            
+            // Statistics (count only the active area, or the whole universe when unbounded)
             ui.horizontal(|ui| {
-                ui.label(format!("Live cells: {}", live_cells));
-                ui.label(format!("Dead cells: {}", 2500 - live_cells));
-                ui.label(format!("Population: {:.1}%", (live_cells as f32 / 2500.0) * 100.0));
+                if let Some(universe) = &snapshot.universe {
+                    ui.label(format!("Live cells: {}", universe.population));
+                    match universe.bounds {
+                        Some(bounds) => ui.label(format!(
+                            "Bounding box: {}x{} from ({}, {}) to ({}, {})",
+                            bounds.width(), bounds.height(), bounds.top, bounds.left, bounds.bottom, bounds.right,
+                        )),
+                        None => ui.label("Bounding box: empty"),
+                    };
+                    if let Some(limit) = universe.limit {
+                        ui.colored_label(Color32::RED, format!("Stopped: {}", limit));
+                    }
+                } else {
+                    let live_cells = grid.count_live_cells();
+                    let total_cells = grid.active_cells();
+                    
+                    ui.label(format!("Live cells: {}", live_cells));
+                    ui.label(format!("Dead cells: {}", total_cells - live_cells));
+                    ui.label(format!("Population: {:.1}%", (live_cells as f32 / total_cells as f32) * 100.0));
+                }
+                
+                ui.separator();
+                
+                // Classified automatically when a cycle is entered, or on demand
+                let hover = format!("Runs the pattern forward on an unbounded plane for up to {} generations", classify::MAX_GENERATIONS);
+                if ui.button("🔍 Classify").on_hover_text(hover).clicked() {
+                    self.simulation.apply(|world| world.classify());
+                }
+                if snapshot.classifying {
+                    ui.spinner();
+                }
+                if let Some(analysis) = &snapshot.analysis {
+                    // A bounded grid's edges are ignored, so say the answer is for the cells on an infinite plane
+                    match snapshot.universe {
+                        Some(_) => ui.label(analysis.to_string()),
+                        None => ui.label(format!("{} on an infinite plane", analysis)),
+                    };
+                }
+                
+                ui.separator();
+                
+                // Splits the cells into objects and counts them by name, also taken when a cycle is entered
+                if ui.button("📋 Census").on_hover_text("Counts the separate objects by name, like the ash of a soup search").clicked() {
+                    self.simulation.apply(|world| world.take_census());
+                }
+                if snapshot.counting {
+                    ui.spinner();
+                }
+                
+                ui.separator();
+                
+                ui.checkbox(&mut self.show_history, "📈 History");
             });
+            
+            // Population, births, deaths and changed cells per generation
+            if self.show_history {
+                ui.separator();
+                self.history_chart(ui, &snapshot.history);
+            }
         });
-        
-        // Request repaint if running to keep animation smooth
-        if self.is_running {
-            ctx.request_repaint();
-        }
     }
 }
\ No newline at end of file
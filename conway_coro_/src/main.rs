@@ -1,19 +1,29 @@
 // main.rs - Time-Sliced Async Conway's Game of Life with Row Coroutines
-// Uses existing patterns.rs and ui.rs modules
+// Grid, patterns and the row coroutine engine come from life_core
+// The world runs on a simulation thread, the window only draws its snapshots and sends commands
 
 use eframe::egui;
 use egui::Color32;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
 
-mod grid;      // Grid types
-mod ui;        // Your existing ui.rs module
-mod patterns;  // Your existing patterns.rs module
+use life_core::{cycle, grid, patterns, Boundary, CancelToken, Command, Cycle, GameOfLifeInterface, Rule, Simulation, SimulationHandle, SimulationThread, TGrid, GRID_START};
+use life_core::age::CellAges;
+use life_core::budget::BudgetController;
+use life_core::census::{self, CensusEntry};
+use life_core::classify::{self, Analysis, Classification};
+use life_core::coroutine_engine::{GenerationProcessor, GenerationStats, PartialGeneration, SharedScheduler};
+use life_core::cycle::CycleDetector;
+use life_core::history::{Activity, ActivityHistory};
+use life_core::palette::RenderStyle;
+use life_core::scheduler::SchedulingPolicy;
 
-use grid::{TGrid, GRID_START, GRID_END, GRID_SIZE, TOTAL_SIZE};
+mod ui;
 
 fn main() -> Result<(), eframe::Error> {
+    // Optional `--size WIDTHxHEIGHT`, defaults to 50x50
+    let (width, height) = grid::size_from_args();
+    
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([800.0, 950.0]),
@@ -23,258 +33,511 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Time-Sliced Conway's Game of Life",
         options,
-        Box::new(|_cc| Box::new(GameOfLife::default())),
+        Box::new(move |cc| Box::new(Universes::new(width, height, &cc.egui_ctx))),
     )
 }
 
-/// Factory function that creates time-sliced row coroutine closures
-fn create_time_sliced_row_coroutine(row_index: usize) -> impl FnMut(TGrid, Duration) -> std::pin::Pin<Box<dyn std::future::Future<Output = (bool, [bool; TOTAL_SIZE])>>> {
-    let mut current_col = GRID_START;
-    let mut result = [false; TOTAL_SIZE];
-    
-    move |current_grid: TGrid, time_budget: Duration| {
-        Box::pin(async move {
-            let start = Instant::now();
-            
-            while current_col < GRID_END {
-                // Check if time budget is exhausted
-                if start.elapsed() >= time_budget {
-                    break;  // Time's up, exit and yield control
-                }
-                
-                let col = current_col;
-                let mut count = 0;
-                
-                // Baked-in neighbor positions for this specific row
-                let neighbors = [
-                    (row_index-1,col-1),(row_index-1,col),(row_index-1,col+1),
-                    (row_index,col-1),                    (row_index,col+1),
-                    (row_index+1,col-1),(row_index+1,col),(row_index+1,col+1)
-                ];
-                
-                for &(nr, nc) in &neighbors {
-                    if current_grid[nr][nc] { count += 1; }
-                }
-                
-                let current_alive = current_grid[row_index][col];
-                
-                let next_state = match (current_alive, count) {
-                    (true, 2) | (true, 3) => true,   // Survival
-                    (false, 3)            => true,   // Birth
-                    _                     => false,  // Death or stays dead
-                };
-                
-                result[col] = next_state;
-                current_col += 1;
-            }
-            
-            // Check if row is complete - just return the status
-            let is_complete = current_col >= GRID_END;
-            (is_complete, result)
-        })
-    }
-}
-
-/// Generation processor that manages time-sliced closure-based coroutines
-struct GenerationProcessor {
-    row_coroutines: Vec<Box<dyn FnMut(TGrid, Duration) -> std::pin::Pin<Box<dyn std::future::Future<Output = (bool, [bool; TOTAL_SIZE])>>>>>,
-    time_budget_per_slice: Duration,
-}
-
-impl GenerationProcessor {
-    fn new(time_budget_per_slice: Duration) -> Self {
-        let mut row_coroutines = Vec::new();
-        
-        // Create coroutines for active rows only (GRID_START..GRID_END)
-        for row in GRID_START..GRID_END {
-            let coroutine = create_time_sliced_row_coroutine(row);
-            row_coroutines.push(Box::new(coroutine) as Box<dyn FnMut(TGrid, Duration) -> std::pin::Pin<Box<dyn std::future::Future<Output = (bool, [bool; TOTAL_SIZE])>>>>);
-        }
-        
-        Self {
-            row_coroutines,
-            time_budget_per_slice,
-        }
-    }
-    
-    async fn process_generation(&mut self, current_grid: TGrid) -> TGrid {
-        let active_rows = GRID_END - GRID_START;  // Should be GRID_SIZE
-        let mut completed_rows = vec![false; active_rows];  // Track which rows are done
-        let mut results = vec![[false; TOTAL_SIZE]; active_rows];    // Store completed row results
-        
-        // Keep giving time slices until all rows complete
-        while !completed_rows.iter().all(|&done| done) {
-            for (i, row_coroutine) in self.row_coroutines.iter_mut().enumerate() {
-                if !completed_rows[i] {
-                    let (is_complete, row_result) = row_coroutine(current_grid, self.time_budget_per_slice).await;
-                    
-                    if is_complete {
-                        completed_rows[i] = true;
-                        results[i] = row_result;
-                    }
-                }
-            }
-        }
-        
-        // Collect results into new grid
-        self.collect_results(results)
-    }
-    
-    fn collect_results(&self, results: Vec<[bool; TOTAL_SIZE]>) -> TGrid {
-        let mut next_grid = [[false; TOTAL_SIZE]; TOTAL_SIZE];
-        for (i, row_result) in results.iter().enumerate() {
-            let row_index = i + GRID_START;  // Map back to active range (GRID_START..GRID_END)
-            next_grid[row_index] = *row_result;
-        }
-        next_grid
-    }
-    
-    fn set_time_budget(&mut self, new_budget: Duration) {
-        self.time_budget_per_slice = new_budget;
-    }
-}
-
-/// Time-Sliced Conway's Game of Life
-pub struct GameOfLife {
+/// Time-Sliced Conway's Game of Life, owned by the simulation thread
+pub struct World {
     current_grid: TGrid,
     
-    pub grid: TGrid,  // Cached copy for UI rendering
     pub is_running: bool,
-    pub last_update: Instant,
     pub update_interval: Duration,
     pub generation: u32,
-    pub live_color: Color32,
-    pub dead_color: Color32,
     pub selected_pattern: usize,
+    pub rule: Rule,
+    pub boundary: Boundary,
+    pub seed: u32,           // Random soup this universe starts from
     
-    runtime: tokio::runtime::Runtime,
+    runtime: Arc<tokio::runtime::Runtime>,  // Shared by every universe in the window
     generation_processor: GenerationProcessor,
+    cancel: CancelToken,   // Tripped by edits sent while a generation is being computed
     
     // Cycle detection
-    grid_history: [u64; 10],
-    history_count: usize,
+    cycle_detector: CycleDetector,   // Exact period and start of the cycle the run has entered
+    pub analysis: Option<Analysis>,  // What the pattern was classified as, until the cells change
+    classifying: Option<CancelToken>,  // Set while a classification runs in the background, cancelled when the cells change
+    pub census: Option<(u32, Vec<CensusEntry>)>,  // Generation the objects were counted at and their counts
+    counting: Option<CancelToken>,   // Set while a census runs in the background, cancelled when the cells change
+    history: Arc<ActivityHistory>,   // Population and activity since generation 0, shared with the snapshots
+    ages: Option<Arc<CellAges>>,     // Ages of the cells, tracked while the UI shades cells by them
     
     // Time slice control
-    pub time_slice_ms: f32,  // Exposed for UI control
+    pub budget: BudgetController,  // Sizes the slices from how long generations really take
+    
+    // Parallel row bands
+    pub threads: usize,      // Row bands, each can run on its own driver of the shared scheduler
+    
+    // Scheduling experiment
+    pub policy: SchedulingPolicy,  // Which unfinished row gets the next slice
+    pub progressive: bool,         // Share each generation while it is computed so the UI can draw it
+    
+    handle: Option<SimulationHandle<World>>,  // Hands background results back to the simulation thread
 }
 
-impl Default for GameOfLife {
-    fn default() -> Self {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let time_slice_ms = 2.0;  // 2ms default time slices
-        let generation_processor = GenerationProcessor::new(Duration::from_millis(time_slice_ms as u64));
+/// What the UI draws, published by the simulation thread after every change
+pub struct Snapshot {
+    pub grid: TGrid,
+    pub generation: u32,
+    pub is_running: bool,
+    pub update_interval: Duration,
+    pub rule: Rule,
+    pub boundary: Boundary,
+    pub seed: u32,
+    pub cycle: Option<Cycle>,         // Set once the generations repeat, which also pauses the run
+    pub analysis: Option<Analysis>,
+    pub classifying: bool,
+    pub census: Option<(u32, Vec<CensusEntry>)>,
+    pub counting: bool,
+    pub history: Arc<ActivityHistory>,
+    pub ages: Option<Arc<CellAges>>,
+    pub rotor: Vec<(usize, usize)>,   // Display (row, col) of the classified oscillator's rotor cells
+    pub budget: BudgetController,
+    pub threads: usize,
+    pub policy: SchedulingPolicy,
+    pub progressive: bool,
+    pub stats: Arc<GenerationStats>,  // Slices of the last generation, empty before the first
+    pub progress: Option<Arc<PartialGeneration>>,  // Live view of the generation being computed, when progressive
+}
+
+impl World {
+    /// A world whose row coroutines are driven by `scheduler` alongside every other universe's
+    pub fn new(width: usize, height: usize, runtime: Arc<tokio::runtime::Runtime>, scheduler: SharedScheduler, seed: u32) -> Self {
+        let update_interval = Duration::from_millis(200);
+        let budget = BudgetController::new(update_interval, height);
+        let rule = Rule::default();
+        let boundary = Boundary::default();
+        let mut generation_processor = GenerationProcessor::new(width, height, rule, boundary, budget.budget());
+        generation_processor.set_shared_scheduler(Some(scheduler));
         
         Self {
-            current_grid: [[false; TOTAL_SIZE]; TOTAL_SIZE],
-            grid: [[false; TOTAL_SIZE]; TOTAL_SIZE],
+            current_grid: TGrid::new(width, height),
             is_running: false,
-            last_update: Instant::now(),
-            update_interval: Duration::from_millis(200),
+            update_interval,
             generation: 0,
-            live_color: Color32::from_rgb(0, 200, 0),
-            dead_color: Color32::from_rgb(40, 40, 40),
             selected_pattern: 0,
+            rule,
+            boundary,
+            seed,
             runtime,
             generation_processor,
-            grid_history: [0; 10],
-            history_count: 0,
-            time_slice_ms,
+            cancel: CancelToken::default(),
+            cycle_detector: CycleDetector::default(),
+            analysis: None,
+            classifying: None,
+            census: None,
+            counting: None,
+            history: Arc::default(),
+            ages: None,
+            budget,
+            threads: 1,
+            policy: SchedulingPolicy::default(),
+            progressive: false,
+            handle: None,
         }
     }
 }
 
-/// Same interface as original - required by existing UI module
-pub trait GameOfLifeInterface {
-    fn update_generation(&mut self);
-    fn hash_grid(&self) -> u64;
-    fn check_for_cycle(&mut self) -> bool;
-    fn clear_grid(&mut self);
-    fn apply_selected_pattern(&mut self);
-    fn check_border_cells_dead(&self) -> bool;
-}
-
-impl GameOfLifeInterface for GameOfLife {
+impl GameOfLifeInterface for World {
+    /// Computes one generation on the shared scheduler while the UI keeps drawing the last snapshot
+    /// An edit arriving meanwhile cancels it, the cells stay as they were and the edit is applied to them
     fn update_generation(&mut self) {
-        // Calculate adaptive time slice based on frame rate: (1/fps)/(N*10)
-        let fps = 1000.0 / self.update_interval.as_millis() as f32;
-        self.time_slice_ms = (1.0 / fps) / (GRID_SIZE as f32 * 10.0) * 1000.0;
+        // The frame deadline is the update interval, the controller picks the slice budget to finish within it
+        self.budget.set_target(self.update_interval, self.current_grid.height());
+        self.generation_processor.set_time_budget(self.budget.budget());
+        self.generation_processor.set_deadline(self.update_interval);
         
-        let time_budget = Duration::from_millis(self.time_slice_ms as u64);
-        self.generation_processor.set_time_budget(time_budget);
+        // Process generation with time-sliced coroutines, only completed generations are measured
+        let started = Instant::now();
+        let current_grid = Arc::new(self.current_grid.clone());
+        // This thread only waits, the slices run on the shared scheduler's drivers
+        let next_grid = self.runtime.block_on(self.generation_processor.process_generation_cancellable(current_grid, &self.cancel));
+        let Some(next_grid) = next_grid else { return; };
+        self.budget.record(started.elapsed());
         
-        self.runtime.block_on(async {
-            // Process generation with time-sliced coroutines
-            let (next_grid, any_incomplete) = self.generation_processor.process_generation(self.current_grid).await;
-            
-            self.current_grid = next_grid;
-            self.grid = self.current_grid;
-            self.generation += 1;
-            self.incomplete_coroutines = any_incomplete;  // Track incomplete coroutines for UI
-        });
+        let activity = Activity::between(u64::from(self.generation) + 1, &self.current_grid, &next_grid);
+        Arc::make_mut(&mut self.history).record(activity);
+        if let Some(ages) = &mut self.ages {
+            Arc::make_mut(ages).update(&next_grid);
+        }
+        self.current_grid = next_grid;
+        self.generation += 1;
         
-        if self.check_for_cycle() { self.is_running = false; }
+        // Pause, classify the pattern and count what it has settled into when a cycle is first entered
+        let was_cycling = self.cycle_detector.cycle().is_some();
+        if self.check_for_cycle().is_some() {
+            self.is_running = false;
+            if !was_cycling {
+                self.classify();
+                self.take_census();
+            }
+        }
     }
     
     fn hash_grid(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        for row in GRID_START..GRID_END {
-            for col in GRID_START..GRID_END {
-                self.grid[row][col].hash(&mut hasher);
-            }
-        }
-        hasher.finish()
+        cycle::hash_grid(&self.current_grid)
     }
     
-    fn check_for_cycle(&mut self) -> bool {
+    fn check_for_cycle(&mut self) -> Option<Cycle> {
         let current_hash = self.hash_grid();
-        if self.grid_history.contains(&current_hash) { return true; }
-        self.grid_history[self.history_count % 10] = current_hash;
-        self.history_count += 1;
-        false
+        self.cycle_detector.observe(u64::from(self.generation), current_hash, self.current_grid.count_live_cells() as u64, &self.current_grid)
     }
     
     fn clear_grid(&mut self) {
-        self.current_grid = [[false; TOTAL_SIZE]; TOTAL_SIZE];
-        self.grid = self.current_grid;
+        self.current_grid.clear();
         self.generation = 0;
-        self.grid_history = [0; 10];
-        self.history_count = 0;
+        self.history = Arc::default();
+        self.reset_cycle();
     }
     
     fn apply_selected_pattern(&mut self) {
         if let Some(pattern) = patterns::PATTERNS.get(self.selected_pattern) {
             patterns::apply_pattern(&mut self.current_grid, pattern);
-            self.grid = self.current_grid;
             self.generation = 0;
-            self.grid_history = [0; 10];
-            self.history_count = 0;
+            self.history = Arc::default();
+            self.reset_cycle();
         }
     }
     
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.generation_processor.set_rule(rule);
+        self.reset_cycle();
+    }
+    
+    fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.generation_processor.set_boundary(boundary);
+        self.reset_cycle();
+    }
+    
     fn check_border_cells_dead(&self) -> bool {
-        for i in 0..TOTAL_SIZE {
-            if self.grid[0][i] != false { panic!("Top border cell [0, {}] should be false", i); }
-            if self.grid[TOTAL_SIZE-1][i] != false { panic!("Bottom border cell [{}, {}] should be false", TOTAL_SIZE-1, i); }
-            if self.grid[i][0] != false { panic!("Left border cell [{}, 0] should be false", i); }
-            if self.grid[i][TOTAL_SIZE-1] != false { panic!("Right border cell [{}, {}] should be false", i, TOTAL_SIZE-1); }
+        self.current_grid.check_border_cells_dead()
+    }
+}
+
+impl Simulation for World {
+    type Snapshot = Snapshot;
+    
+    fn snapshot(&self) -> Snapshot {
+        let rotor = match &self.analysis {
+            Some(Analysis { classification: Classification::Oscillator { rotor, .. }, .. }) => rotor.iter()
+                .filter_map(|&(row, col)| Some((usize::try_from(row).ok()?, usize::try_from(col).ok()?)))
+                .filter(|&(row, col)| self.current_grid.contains(row, col))  // The unbounded run can leave the grid
+                .map(|(row, col)| (row - GRID_START, col - GRID_START))
+                .collect(),
+            _ => Vec::new(),
+        };
+        
+        Snapshot {
+            grid: self.current_grid.clone(),
+            generation: self.generation,
+            is_running: self.is_running,
+            update_interval: self.update_interval,
+            rule: self.rule,
+            boundary: self.boundary,
+            seed: self.seed,
+            cycle: self.cycle_detector.cycle(),
+            analysis: self.analysis.clone(),
+            classifying: self.classifying.is_some(),
+            census: self.census.clone(),
+            counting: self.counting.is_some(),
+            history: self.history.clone(),
+            ages: self.ages.clone(),
+            rotor,
+            budget: self.budget,
+            threads: self.threads,
+            policy: self.policy,
+            progressive: self.progressive,
+            stats: self.generation_processor.last_stats(),
+            progress: self.generation_processor.progress(),
         }
-        true
+    }
+    
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    
+    fn set_running(&mut self, running: bool) {
+        self.is_running = running;
+    }
+    
+    fn update_interval(&self) -> Duration {
+        self.update_interval
+    }
+    
+    fn toggle_cell(&mut self, display_row: usize, display_col: usize) {
+        let (row, col) = (display_row + GRID_START, display_col + GRID_START);  // Map to the active area
+        if self.current_grid.contains(row, col) {
+            self.current_grid.toggle(row, col);
+            self.reset_cycle();
+        }
+    }
+    
+    fn load_pattern(&mut self, index: usize) {
+        self.selected_pattern = index;
+        self.apply_selected_pattern();
+    }
+    
+    fn cancel_token(&self) -> Option<CancelToken> {
+        Some(self.cancel.clone())
+    }
+    
+    fn attach(&mut self, handle: SimulationHandle<World>) {
+        self.handle = Some(handle);
     }
 }
 
-impl GameOfLife {
+impl World {
+    /// Forgets the generations seen so far, what they were classified as and counted, after anything that changes the cells other than a step
+    fn reset_cycle(&mut self) {
+        self.cycle_detector.reset();
+        // The cells as they are now are the first generation the detector sees, so a cycle can start from them
+        self.check_for_cycle();
+        self.stop_classifying();
+        self.stop_counting();
+        if self.ages.is_some() {
+            self.set_track_ages(true);
+        }
+    }
+    
+    /// Starts or stops tracking cell ages, for the renderings that shade cells by them
+    pub fn set_track_ages(&mut self, track: bool) {
+        self.ages = track.then(|| Arc::new(CellAges::new(&self.current_grid)));
+    }
+    
+    /// Runs the current cells forward on an unbounded plane to name what they are, unless there are too many
+    /// The run takes a thread of its own so generations and edits carry on meanwhile, the analysis arrives as a command
+    pub fn classify(&mut self) {
+        self.stop_classifying();
+        if self.current_grid.count_live_cells() as u64 > classify::MAX_POPULATION { return; }
+        let Some(handle) = &self.handle else { return; };
+        
+        let cancel = CancelToken::default();
+        let (cells, rule) = (classify::grid_cells(&self.current_grid), self.rule);
+        handle.spawn_work(
+            cancel.clone(),
+            move |cancel| classify::classify_cancellable(cells, rule, classify::MAX_GENERATIONS, cancel),
+            |world, analysis| {
+                world.analysis = Some(analysis);
+                world.classifying = None;
+            },
+        );
+        self.classifying = Some(cancel);
+    }
+    
+    /// Forgets the analysis and abandons one still running
+    fn stop_classifying(&mut self) {
+        if let Some(cancel) = self.classifying.take() {
+            cancel.cancel();
+        }
+        self.analysis = None;
+    }
+    
+    /// Splits the current cells into objects and counts them by name, unless there are too many
+    /// Counted on a thread of its own like a classification, the entries arrive as a command
+    pub fn take_census(&mut self) {
+        self.stop_counting();
+        if self.current_grid.count_live_cells() as u64 > classify::MAX_POPULATION { return; }
+        let Some(handle) = &self.handle else { return; };
+        
+        let cancel = CancelToken::default();
+        let (cells, rule, generation) = (classify::grid_cells(&self.current_grid), self.rule, self.generation);
+        handle.spawn_work(
+            cancel.clone(),
+            move |cancel| census::census_cancellable(cells, rule, cancel),
+            move |world, entries| {
+                world.census = Some((generation, entries));
+                world.counting = None;
+            },
+        );
+        self.counting = Some(cancel);
+    }
+    
+    /// Forgets the census and abandons one still running
+    fn stop_counting(&mut self) {
+        if let Some(cancel) = self.counting.take() {
+            cancel.cancel();
+        }
+        self.census = None;
+    }
+    
     pub fn apply_random_pattern_async(&mut self) {
-        patterns::apply_random_pattern(&mut self.current_grid, self.generation);
-        self.grid = self.current_grid;
+        patterns::apply_random_pattern(&mut self.current_grid, self.seed);
         self.generation = 0;
-        self.grid_history = [0; 10];
-        self.history_count = 0;
+        self.history = Arc::default();
+        self.reset_cycle();
+    }
+    
+    /// Splits the rows into this many bands, the shared scheduler can run each on a driver of its own
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+        self.generation_processor.set_threads(self.threads);
+    }
+    
+    /// Changes which row coroutine gets each time slice, the cells come out the same
+    pub fn set_policy(&mut self, policy: SchedulingPolicy) {
+        self.policy = policy;
+        self.generation_processor.set_policy(policy);
     }
     
-    pub fn toggle_cell_async(&mut self, row: usize, col: usize) {
-        if row >= GRID_START && row < GRID_END && col >= GRID_START && col < GRID_END {
-            self.current_grid[row][col] = !self.current_grid[row][col];
-            self.grid = self.current_grid;
+    /// Turns on drawing each generation row by row as the coroutines finish it
+    pub fn set_progressive(&mut self, progressive: bool) {
+        self.progressive = progressive;
+        self.generation_processor.set_progressive(progressive);
+    }
+    
+    /// Changes the active area, keeping the cells that still fit
+    pub fn resize_grid(&mut self, width: usize, height: usize) {
+        self.generation_processor.resize(width, height);
+        self.current_grid.resize(width, height);
+        self.reset_cycle();
+    }
+}
+
+/// One universe of the Time-Sliced Conway's Game of Life window
+/// The world runs on its own thread, the UI draws its latest snapshot and sends commands back
+pub struct GameOfLife {
+    pub simulation: SimulationThread<World>,
+    pub live_color: Color32,
+    pub dead_color: Color32,
+    pub rotor_color: Color32,  // Highlights the rotor of a classified oscillator
+    pub render: RenderStyle,   // Shading by cell age, recent activity or trails, with its colour ramp
+    pub selected_pattern: usize,      // Pattern chosen in the dropdown, loaded by Apply Pattern
+    pub rule_text: String,            // Rulestring being edited in the UI
+    pub rule_error: Option<String>,   // Why the last typed rulestring was rejected
+    pub new_width: usize,   // Pending size from the resize controls
+    pub new_height: usize,
+    pub show_stats: bool,   // Side panel with the slice timings of the last generation
+    pub show_history: bool,              // Population and activity chart under the grid
+    pub export_status: Option<String>,   // Where the history was last exported to, or why it failed
+}
+
+impl GameOfLife {
+    pub fn new(world: World, ctx: &egui::Context) -> Self {
+        let (width, height) = (world.current_grid.width(), world.current_grid.height());
+        let rule_text = world.rule.to_string();
+        
+        // Each published snapshot wakes the UI, so nothing polls while the simulation is idle
+        let ctx = ctx.clone();
+        Self {
+            simulation: SimulationThread::spawn(world, move || ctx.request_repaint()),
+            live_color: Color32::from_rgb(0, 200, 0),
+            dead_color: Color32::from_rgb(40, 40, 40),
+            rotor_color: Color32::from_rgb(220, 60, 200),
+            render: RenderStyle::default(),
+            selected_pattern: 0,
+            rule_text,
+            rule_error: None,
+            new_width: width,
+            new_height: height,
+            show_stats: false,
+            show_history: true,
+            export_status: None,
+        }
+    }
+    
+    /// Switches rule on the simulation thread and shows it in the rulestring box
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule_text = rule.to_string();
+        self.rule_error = None;
+        self.simulation.edit(move |world| world.set_rule(rule));
+    }
+}
+
+/// Most universes the window tiles at once
+pub const MAX_UNIVERSES: usize = 9;
+
+/// Run control from a universe's buttons, sent to every universe when synchronised
+#[derive(Clone, Copy)]
+pub enum RunControl {
+    Start,
+    Pause,
+    Step,
+}
+
+impl RunControl {
+    fn command(self) -> Command<World> {
+        match self {
+            RunControl::Start => Command::Start,
+            RunControl::Pause => Command::Pause,
+            RunControl::Step  => Command::Step,
+        }
+    }
+}
+
+/// The window: universes side by side, each on its own simulation thread, all on one tokio runtime and scheduler
+pub struct Universes {
+    pub universes: Vec<GameOfLife>,
+    pub synchronised: bool,   // Start, pause and step in one universe act on all of them
+    runtime: Arc<tokio::runtime::Runtime>,
+    scheduler: SharedScheduler,   // Interleaves the row slices of every universe on the runtime
+    size: (usize, usize),     // Grid size of newly added universes
+    ctx: egui::Context,
+}
+
+impl Universes {
+    pub fn new(width: usize, height: usize, ctx: &egui::Context) -> Self {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        // One driver per worker thread, so bands of every universe can run in parallel
+        let scheduler = SharedScheduler::new(runtime.handle(), runtime.metrics().num_workers());
+        let mut universes = Self {
+            universes: Vec::new(),
+            synchronised: false,
+            runtime: Arc::new(runtime),
+            scheduler,
+            size: (width, height),
+            ctx: ctx.clone(),
+        };
+        universes.set_count(1);
+        universes
+    }
+    
+    /// Adds universes with the next seeds or drops the last ones, between 1 and `MAX_UNIVERSES`
+    pub fn set_count(&mut self, count: usize) {
+        let count = count.clamp(1, MAX_UNIVERSES);
+        self.universes.truncate(count);
+        while self.universes.len() < count {
+            let (width, height) = self.size;
+            let world = World::new(width, height, self.runtime.clone(), self.scheduler.clone(), self.universes.len() as u32);
+            self.universes.push(GameOfLife::new(world, &self.ctx));
+        }
+    }
+    
+    /// Sends `control` to the universe at `index`, or to all of them when synchronised
+    pub fn run_control(&self, index: usize, control: RunControl) {
+        if self.synchronised {
+            self.run_all(control);
+        } else {
+            self.universes[index].simulation.send(control.command());
+        }
+    }
+    
+    pub fn run_all(&self, control: RunControl) {
+        for universe in &self.universes {
+            universe.simulation.send(control.command());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn blinker_cycle_starts_at_the_loaded_generation() {
+        let runtime = Arc::new(tokio::runtime::Runtime::new().unwrap());
+        let scheduler = SharedScheduler::new(runtime.handle(), 1);
+        let mut world = World::new(10, 10, runtime, scheduler, 0);
+        let blinker = patterns::PATTERNS.iter().position(|pattern| pattern.name == "Blinker").unwrap();
+        world.load_pattern(blinker);
+        
+        // Running stops once the cycle is confirmed, a few generations in
+        world.set_running(true);
+        while world.is_running && world.generation < 10 {
+            world.update_generation();
         }
+        let cycle = world.cycle_detector.cycle().expect("a blinker repeats after two generations");
+        assert_eq!((cycle.period, cycle.start), (2, 0));
     }
 }
\ No newline at end of file
@@ -3,161 +3,655 @@
 
 use eframe::egui;
 use egui::{Color32, Rect, Stroke, Vec2};
-use std::time::{Duration, Instant};
-use crate::{GameOfLife, patterns, GameOfLifeInterface};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Duration;
+use life_core::{patterns, Command, GameOfLifeInterface, BOUNDARIES, GRID_START, RULES};
+use life_core::age::MAX_HEAT_WINDOW;
+use life_core::budget::ControllerState;
+use life_core::classify;
+use life_core::coroutine_engine::{GenerationStats, SliceRecord};
+use life_core::history::{self, ActivityHistory};
+use life_core::palette::{RenderMode, RAMPS, RENDER_MODES};
+use life_core::scheduler::SCHEDULING_POLICIES;
+use crate::{GameOfLife, RunControl, Snapshot, Universes, MAX_UNIVERSES};
 
-impl eframe::App for GameOfLife {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Auto-update if running
-        if self.is_running && self.last_update.elapsed() >= self.update_interval {
-            self.update_generation();
-            self.last_update = Instant::now();
-            ctx.request_repaint(); // Ensure continuous updates
+/// Height of each chart in the slice panel
+const CHART_HEIGHT: f32 = 280.0;
+
+/// Height of the population and activity chart under each grid
+const HISTORY_HEIGHT: f32 = 80.0;
+
+/// Most recent generations on the history chart, older ones scroll off to the left
+const HISTORY_WINDOW: usize = 300;
+
+/// Colours of the population, births, deaths and changed-cell lines, in the order of `history::SERIES`
+const HISTORY_COLORS: [Color32; 4] = [
+    Color32::from_rgb(0, 200, 0),
+    Color32::from_rgb(80, 160, 255),
+    Color32::from_rgb(230, 80, 80),
+    Color32::from_rgb(230, 200, 60),
+];
+
+/// Progressive rendering: brightness of cells computed in rows that are not finished yet
+const PARTIAL_ROW_TINT: f32 = 0.7;
+
+/// Progressive rendering: brightness of cells still showing the previous generation
+const PENDING_TINT: f32 = 0.3;
+
+/// Milliseconds with enough digits for sub-millisecond slices
+fn ms(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl GameOfLife {
+    /// Scrolling line chart of the recent population and activity, under the latest values and the CSV export to `csv_path`
+    fn history_chart(&mut self, ui: &mut egui::Ui, history: &ActivityHistory, csv_path: &str) {
+        let samples = history.samples();
+        let recent = samples.range(samples.len().saturating_sub(HISTORY_WINDOW)..);
+        
+        ui.horizontal_wrapped(|ui| {
+            for ((name, series), color) in history::SERIES.into_iter().zip(HISTORY_COLORS) {
+                let latest = samples.back().and_then(series).map_or("-".to_string(), |count| count.to_string());
+                ui.colored_label(color, format!("{}: {}", name, latest));
+            }
+            
+            ui.separator();
+            
+            let hover = format!("Writes every recorded generation, up to the last {}", history::HISTORY_LENGTH);
+            if ui.button("💾 Export CSV").on_hover_text(hover).clicked() {
+                let written = File::create(csv_path).and_then(|file| history.write_csv(BufWriter::new(file)));
+                self.export_status = Some(match written {
+                    Ok(()) => format!("Saved {} generations to {}", samples.len(), csv_path),
+                    Err(error) => format!("Export failed: {}", error),
+                });
+            }
+            if let Some(status) = &self.export_status {
+                ui.label(status);
+            }
+        });
+        
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), HISTORY_HEIGHT), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+        
+        // One scale for every series, the largest count in view reaches the top
+        let peak = recent.clone()
+            .flat_map(|activity| history::SERIES.map(|(_, series)| series(activity).unwrap_or(0)))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let x = |index: usize| rect.left() + rect.width() * index as f32 / (HISTORY_WINDOW - 1) as f32;
+        let y = |count: u64| rect.bottom() - rect.height() * count as f32 / peak as f32;
+        for ((_, series), color) in history::SERIES.into_iter().zip(HISTORY_COLORS) {
+            let points: Vec<egui::Pos2> = recent.clone()
+                .enumerate()
+                .filter_map(|(index, activity)| series(activity).map(|count| egui::pos2(x(index), y(count))))
+                .collect();
+            painter.add(egui::Shape::line(points, Stroke::new(1.5, color)));
         }
+        painter.text(rect.left_top() + Vec2::splat(4.0), egui::Align2::LEFT_TOP, peak.to_string(), egui::FontId::monospace(10.0), Color32::GRAY);
+    }
+    
+    /// Side panel showing what the row coroutines did during the last generation
+    fn stats_panel(&self, ui: &mut egui::Ui, stats: &GenerationStats) {
+        ui.heading("Last generation");
         
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Async Conway's Game of Life (Row Coroutines)");
+        if stats.rows.is_empty() {
+            ui.label("No generation computed yet.");
+            return;
+        }
+        
+        ui.label(format!("Wall time: {}", ms(stats.wall_time)));
+        ui.label(format!("Slices: {} ({} expired mid-row)", stats.total_slices(), stats.total_expired()));
+        if stats.cancelled {
+            ui.colored_label(Color32::from_rgb(230, 140, 0), "Cancelled by an edit");
+        }
+        if stats.dropped_slices > 0 {
+            ui.label(format!("{} slices not shown on the timeline", stats.dropped_slices));
+        }
+        
+        ui.separator();
+        
+        // Per-row bar chart: slices taken, the expired part in orange
+        ui.label("Slices per row");
+        let max_slices = stats.rows.iter().map(|row| row.slices).max().unwrap_or(1).max(1);
+        let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), CHART_HEIGHT), egui::Sense::hover());
+        let chart = response.rect;
+        painter.rect_filled(chart, 0.0, Color32::from_gray(20));
+        
+        let lane = chart.height() / stats.rows.len() as f32;
+        for (i, row) in stats.rows.iter().enumerate() {
+            let top = chart.top() + i as f32 * lane;
+            let width = chart.width() * row.slices as f32 / max_slices as f32;
+            let expired_width = chart.width() * row.expired as f32 / max_slices as f32;
+            painter.rect_filled(Rect::from_min_size(egui::pos2(chart.left(), top), Vec2::new(width, lane.max(1.0))), 0.0, self.live_color);
+            painter.rect_filled(Rect::from_min_size(egui::pos2(chart.left(), top), Vec2::new(expired_width, lane.max(1.0))), 0.0, Color32::from_rgb(230, 140, 0));
+        }
+        
+        if let Some(pos) = response.hover_pos() {
+            let i = (((pos.y - chart.top()) / lane) as usize).min(stats.rows.len() - 1);
+            let row = &stats.rows[i];
+            response.on_hover_text(format!(
+                "Row {}: {} slices, {} expired, busy {}, finished at {}",
+                row.row, row.slices, row.expired, ms(row.busy), ms(row.finished_at),
+            ));
+        }
+        
+        ui.separator();
+        
+        // Timeline: one lane per row, time runs left to right, slices that completed their row in green
+        ui.label("Slice timeline");
+        let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), CHART_HEIGHT), egui::Sense::hover());
+        let chart = response.rect;
+        painter.rect_filled(chart, 0.0, Color32::from_gray(20));
+        
+        let first_row = stats.rows[0].row;
+        let scale = chart.width() / stats.wall_time.as_secs_f32().max(f32::EPSILON);
+        let slice_rect = |slice: &SliceRecord| {
+            let left = chart.left() + slice.start.as_secs_f32() * scale;
+            let top = chart.top() + (slice.row - first_row) as f32 * lane;
+            Rect::from_min_size(egui::pos2(left, top), Vec2::new((slice.duration.as_secs_f32() * scale).max(1.0), lane.max(1.0)))
+        };
+        for slice in &stats.slices {
+            let color = if slice.completed { self.live_color } else { Color32::from_rgb(230, 140, 0) };
+            painter.rect_filled(slice_rect(slice), 0.0, color);
+        }
+        
+        if let Some(pos) = response.hover_pos()
+            && let Some(slice) = stats.slices.iter().find(|slice| slice_rect(slice).contains(pos)) {
+            response.on_hover_text(format!(
+                "Row {} on band {}: columns {}..{} from {} for {}{}",
+                slice.row, slice.band, slice.start_col, slice.end_col, ms(slice.start), ms(slice.duration),
+                if slice.completed { ", row complete" } else { ", budget expired" },
+            ));
+        }
+        
+        ui.label(format!("0 to {}, orange slices ran out of budget mid-row", ms(stats.wall_time)));
+    }
+    
+    /// Draws one universe's controls and grid from `snapshot`, in a tile of the window
+    /// Start, pause and step are handed back, they go to every universe when synchronised
+    pub fn show(&mut self, ui: &mut egui::Ui, index: usize, snapshot: &Snapshot) -> Option<RunControl> {
+        let mut run_control = None;
+        ui.strong(format!("Universe {} - {}", index + 1, snapshot.rule));
+        
+        // Controls
+        ui.horizontal_wrapped(|ui| {
+            let button_text = if snapshot.is_running { "⏸ Pause" } else { "▶ Start" };
+            if ui.button(button_text).clicked() {
+                run_control = Some(if snapshot.is_running { RunControl::Pause } else { RunControl::Start });
+            }
             
-            // Controls
-            ui.horizontal(|ui| {
-                let button_text = if self.is_running { "⏸ Pause" } else { "▶ Start" };
-                if ui.button(button_text).clicked() {
-                    self.is_running = !self.is_running;
-                    if self.is_running {
-                        self.last_update = Instant::now();
+            if ui.button("⏭ Step").clicked() {
+                run_control = Some(RunControl::Step);
+            }
+            
+            if ui.button("⏹ Clear").clicked() {
+                self.simulation.send(Command::Pause);
+                self.simulation.edit(|world| world.clear_grid());
+            }
+            
+            if ui.button("🎲 Random").clicked() {
+                self.simulation.send(Command::Pause);
+                self.simulation.edit(|world| world.apply_random_pattern_async()); // Use async version
+            }
+            let mut seed = snapshot.seed;
+            if ui.add(egui::DragValue::new(&mut seed).prefix("seed ")).on_hover_text("Seed of the random soup, the same seed gives the same soup").changed() {
+                self.simulation.apply(move |world| world.seed = seed);
+            }
+            
+            ui.separator();
+            
+            // Pattern dropdown
+            ui.label("Pattern:");
+            egui::ComboBox::from_id_source("pattern_selector")
+                .selected_text(patterns::PATTERNS[self.selected_pattern].name)
+                .show_ui(ui, |ui| {
+                    for (i, pattern) in patterns::PATTERNS.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_pattern, i, pattern.name);
+                    }
+                });
+            
+            if ui.button("Apply Pattern").clicked() {
+                self.simulation.send(Command::Pause);
+                self.simulation.send(Command::LoadPattern(self.selected_pattern));
+            }
+            
+            ui.separator();
+            
+            // Rule dropdown, or type any B/S rulestring and press Enter
+            ui.label("Rule:");
+            let rule_name = RULES.iter()
+                .find(|named| named.rule == snapshot.rule)
+                .map_or("Custom", |named| named.name);
+            egui::ComboBox::from_id_source("rule_selector")
+                .selected_text(rule_name)
+                .show_ui(ui, |ui| {
+                    for named in RULES {
+                        let label = format!("{} ({})", named.name, named.rule);
+                        if ui.selectable_label(snapshot.rule == named.rule, label).clicked() {
+                            self.set_rule(named.rule);
+                        }
                     }
+                });
+            
+            let rule_edit = ui.add(egui::TextEdit::singleline(&mut self.rule_text).desired_width(90.0));
+            if rule_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                match self.rule_text.parse() {
+                    Ok(rule) => self.set_rule(rule),
+                    Err(error) => self.rule_error = Some(error.to_string()),
                 }
-                
-                if ui.button("⏹ Clear").clicked() {
-                    self.is_running = false;
-                    self.clear_grid();
+            }
+            if let Some(error) = &self.rule_error {
+                ui.colored_label(Color32::RED, "⚠").on_hover_text(error);
+            }
+            
+            ui.separator();
+            
+            ui.label(format!("Generation: {}", snapshot.generation));
+            if let Some(cycle) = snapshot.cycle {
+                ui.colored_label(Color32::from_rgb(230, 140, 0), cycle.to_string());
+            }
+        });
+        
+        ui.separator();
+        
+        // Speed control
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Speed:");
+            let mut speed = 1000.0 / snapshot.update_interval.as_millis() as f32;
+            if ui.add(egui::Slider::new(&mut speed, 0.5..=90.0).suffix(" gen/sec")).changed() {
+                let update_interval = Duration::from_millis((1000.0 / speed) as u64);
+                self.simulation.apply(move |world| world.update_interval = update_interval);
+            }
+            
+            ui.separator();
+            
+            // Show current colors
+            ui.label("Live:");
+            ui.color_edit_button_srgba(&mut self.live_color);
+            ui.label("Dead:");
+            ui.color_edit_button_srgba(&mut self.dead_color);
+            ui.label("Rotor:");
+            ui.color_edit_button_srgba(&mut self.rotor_color);
+            
+            ui.separator();
+            
+            // Grid size, applied live and keeping the cells that still fit
+            ui.label("Grid:");
+            ui.add(egui::DragValue::new(&mut self.new_width).clamp_range(1..=1024));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut self.new_height).clamp_range(1..=1024));
+            if ui.button("Resize").clicked() {
+                let (width, height) = (self.new_width, self.new_height);
+                self.simulation.edit(move |world| world.resize_grid(width, height));
+            }
+            
+            ui.separator();
+            
+            // What happens at the edges of the grid
+            ui.label("Edges:");
+            egui::ComboBox::from_id_source("boundary_selector")
+                .selected_text(snapshot.boundary.name())
+                .show_ui(ui, |ui| {
+                    for &boundary in BOUNDARIES {
+                        if ui.selectable_label(snapshot.boundary == boundary, boundary.name()).clicked() {
+                            self.simulation.edit(move |world| world.set_boundary(boundary));
+                        }
+                    }
+                });
+        });
+        
+        ui.separator();
+        
+        // Cell shading by how long cells have lived, how often they changed lately or where they just died
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Render:");
+            let mut mode = self.render.mode;
+            egui::ComboBox::from_id_source("render_selector")
+                .selected_text(mode.name())
+                .show_ui(ui, |ui| {
+                    for &choice in RENDER_MODES {
+                        ui.selectable_value(&mut mode, choice, choice.name());
+                    }
+                });
+            if mode.uses_ages() != self.render.mode.uses_ages() {
+                let track = mode.uses_ages();
+                self.simulation.apply(move |world| world.set_track_ages(track));
+            }
+            self.render.mode = mode;
+            
+            match mode {
+                RenderMode::Plain => {}
+                RenderMode::Age => {
+                    ui.label("Span:");
+                    ui.add(egui::DragValue::new(&mut self.render.age_span).clamp_range(1..=10_000).suffix(" gen"))
+                        .on_hover_text("Generations alive that reach the top of the ramp");
                 }
-                
-                if ui.button("🎲 Random").clicked() {
-                    self.is_running = false;
-                    self.apply_random_pattern_async(); // Use async version
+                RenderMode::Heat => {
+                    ui.label("Window:");
+                    ui.add(egui::DragValue::new(&mut self.render.heat_window).clamp_range(1..=MAX_HEAT_WINDOW).suffix(" gen"))
+                        .on_hover_text("Generations each cell's changes are counted over");
                 }
-                
+                RenderMode::Trails => {
+                    ui.label("Fade:");
+                    ui.add(egui::DragValue::new(&mut self.render.trail_length).clamp_range(1..=1000).suffix(" gen"))
+                        .on_hover_text("Generations a dead cell's trail takes to fade out");
+                }
+            }
+            
+            // Preset ramps, then each of their colours from low to high
+            if mode.uses_ages() {
                 ui.separator();
-                
-                // Pattern dropdown
-                ui.label("Pattern:");
-                egui::ComboBox::from_id_source("pattern_selector")
-                    .selected_text(patterns::PATTERNS[self.selected_pattern].name)
+                ui.label("Ramp:");
+                let ramp_name = RAMPS.iter()
+                    .find(|named| named.ramp == self.render.ramp)
+                    .map_or("Custom", |named| named.name);
+                egui::ComboBox::from_id_source("ramp_selector")
+                    .selected_text(ramp_name)
                     .show_ui(ui, |ui| {
-                        for (i, pattern) in patterns::PATTERNS.iter().enumerate() {
-                            ui.selectable_value(&mut self.selected_pattern, i, pattern.name);
+                        for named in RAMPS {
+                            if ui.selectable_label(self.render.ramp == named.ramp, named.name).clicked() {
+                                self.render.ramp = named.ramp;
+                            }
                         }
                     });
+                ui.color_edit_button_srgb(&mut self.render.ramp.low);
+                ui.color_edit_button_srgb(&mut self.render.ramp.middle);
+                ui.color_edit_button_srgb(&mut self.render.ramp.high);
+            }
+        });
+        
+        ui.separator();
+        
+        // Parallel stepping and scheduling, the result is the same whatever is chosen, only the latency changes
+        ui.horizontal_wrapped(|ui| {
+            let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+            ui.label("Threads:");
+            let mut threads = snapshot.threads;
+            if ui.add(egui::DragValue::new(&mut threads).clamp_range(1..=cores.max(snapshot.threads))).changed() {
+                self.simulation.apply(move |world| world.set_threads(threads));
+            }
+            ui.label(format!("of {} cores, rows split into {} bands", cores, snapshot.threads.min(snapshot.grid.height())));
+            
+            ui.separator();
+            
+            ui.label("Scheduler:");
+            egui::ComboBox::from_id_source("scheduler_selector")
+                .selected_text(snapshot.policy.name())
+                .show_ui(ui, |ui| {
+                    for &policy in SCHEDULING_POLICIES {
+                        if ui.selectable_label(snapshot.policy == policy, policy.name()).clicked() {
+                            self.simulation.apply(move |world| world.set_policy(policy));
+                        }
+                    }
+                });
+            
+            if !snapshot.stats.rows.is_empty() {
+                ui.label(format!("Last generation: {}", ms(snapshot.stats.wall_time)));
+            }
+            ui.checkbox(&mut self.show_stats, "Slice timings");
+            
+            let mut progressive = snapshot.progressive;
+            if ui.checkbox(&mut progressive, "Progressive").on_hover_text("Draw each generation row by row while the coroutines compute it").changed() {
+                self.simulation.apply(move |world| world.set_progressive(progressive));
+            }
+        });
+        
+        // Slice budget controller, aiming each generation at the update interval
+        ui.horizontal_wrapped(|ui| {
+            let budget = &snapshot.budget;
+            ui.label(format!("Target: {}", ms(budget.target())));
+            match (budget.last(), budget.latency()) {
+                (Some(last), Some(latency)) => ui.label(format!("Measured: {} (smoothed {})", ms(last), ms(latency))),
+                _ => ui.label("Measured: -"),
+            };
+            ui.label(format!("Slice budget: {} µs", budget.budget().as_micros()));
+            
+            let color = match budget.state() {
+                ControllerState::AtCeiling => Color32::from_rgb(230, 140, 0),
+                ControllerState::Holding => self.live_color,
+                _ => ui.visuals().text_color(),
+            };
+            ui.colored_label(color, budget.state().name())
+                .on_hover_text("Late generations get longer slices and less overhead, early ones shorter slices and finer interleaving");
+        });
+        
+        ui.separator();
+        
+        // Draw the grid (only show the active area), shrinking cells to fit large grids
+        let grid = &snapshot.grid;
+        let rotor: HashSet<(usize, usize)> = snapshot.rotor.iter().copied().collect();
+        let dead = [self.dead_color.r(), self.dead_color.g(), self.dead_color.b()];
+        let chart_height = if self.show_history { HISTORY_HEIGHT + 30.0 } else { 0.0 };
+        let spacing = 0.5;
+        let (grid_width, grid_height) = (grid.width(), grid.height());
+        let fit = (ui.available_width() / grid_width as f32)
+            .min((ui.available_height() - 40.0 - chart_height) / grid_height as f32);
+        let box_size = (fit - spacing).clamp(1.0, 15.0);
+        
+        let start_pos = ui.cursor().min;
+        let total_size = Vec2::new(
+            (box_size + spacing) * grid_width as f32 - spacing,
+            (box_size + spacing) * grid_height as f32 - spacing,
+        );
+        
+        let (response, painter) = ui.allocate_painter(total_size, egui::Sense::click());
+        
+        // Fill background
+        painter.rect_filled(
+            Rect::from_min_size(start_pos, total_size),
+            0.0,
+            Color32::BLACK,
+        );
+        
+        // While a generation is being computed progressively, draw what is done of it over the current one
+        let partial = snapshot.progress.as_ref()
+            .map(|progress| progress.snapshot())
+            .filter(|partial| partial.in_progress && partial.next_grid.height() == grid_height && partial.next_grid.width() == grid_width);
+        
+        // Draw only the active area
+        for display_row in 0..grid_height {
+            for display_col in 0..grid_width {
+                let (row, col) = (display_row + GRID_START, display_col + GRID_START);  // Map to the active area
+                let x = start_pos.x + display_col as f32 * (box_size + spacing);
+                let y = start_pos.y + display_row as f32 * (box_size + spacing);
                 
-                if ui.button("Apply Pattern").clicked() {
-                    self.is_running = false;
-                    self.apply_selected_pattern();
-                }
+                let rect = Rect::from_min_size(
+                    egui::pos2(x, y),
+                    Vec2::splat(box_size),
+                );
                 
-                ui.separator();
+                // Finished rows show the next generation, unfinished ones the part computed so far and the rest dimmed
+                let (alive, tint) = match &partial {
+                    Some(partial) if partial.row_finished(row) => (partial.next_grid[row][col], 1.0),
+                    Some(partial) if col < partial.cursors[row] => (partial.next_grid[row][col], PARTIAL_ROW_TINT),
+                    Some(_) => (grid[row][col], PENDING_TINT),
+                    None => (grid[row][col], 1.0),
+                };
                 
-                ui.label(format!("Generation: {}", self.generation));
-            });
+                // Ages describe the current generation, so cells already computed for the next one that changed go unshaded
+                let shade = snapshot.ages.as_ref()
+                    .map(|ages| ages.get(display_row, display_col))
+                    .filter(|age| age.alive == alive)
+                    .and_then(|age| self.render.shade(age, dead));
+                
+                // Choose color based on cell state, rotor cells stand out and are dimmed in the phases they are dead
+                let cell_color = match (alive, rotor.contains(&(display_row, display_col)), shade) {
+                    (true, true, _)             => self.rotor_color,
+                    (false, true, _)            => self.rotor_color.linear_multiply(0.3),
+                    (_, false, Some([r, g, b])) => Color32::from_rgb(r, g, b),
+                    (true, false, None)         => self.live_color,
+                    (false, false, None)        => self.dead_color,
+                };
+                
+                painter.rect_filled(rect, 1.0, cell_color.linear_multiply(tint));
+                
+                // Draw subtle border, or the column cursor of an unfinished row
+                if let Some(partial) = &partial
+                    && !partial.row_finished(row) && col == partial.cursors[row] {
+                    painter.rect_stroke(rect, 1.0, Stroke::new(1.5, Color32::YELLOW));
+                } else {
+                    painter.rect_stroke(rect, 1.0, Stroke::new(0.2, Color32::from_gray(60)));
+                }
+                
+                // Handle clicking, applied on the simulation thread after cancelling a generation in flight
+                if response.clicked()
+                    && let Some(pos) = response.interact_pointer_pos()
+                    && rect.contains(pos) {
+                    self.simulation.send(Command::ToggleCell { row: display_row, col: display_col });
+                }
+            }
+        }
+        
+        ui.separator();
+        
+        // Statistics (count only the active area)
+        let live_cells = grid.count_live_cells();
+        let total_cells = grid.active_cells();
+        
+        ui.horizontal(|ui| {
+            ui.label(format!("Live cells: {}", live_cells));
+            ui.label(format!("Dead cells: {}", total_cells - live_cells));
+            ui.label(format!("Population: {:.1}%", (live_cells as f32 / total_cells as f32) * 100.0));
             
             ui.separator();
             
-            // Speed control
+            // Classified automatically when a cycle is entered, or on demand
+            let hover = format!("Runs the pattern forward on an unbounded plane for up to {} generations", classify::MAX_GENERATIONS);
+            if ui.button("🔍 Classify").on_hover_text(hover).clicked() {
+                self.simulation.apply(|world| world.classify());
+            }
+            if snapshot.classifying {
+                ui.spinner();
+            }
+            if let Some(analysis) = &snapshot.analysis {
+                // The grid's edges are ignored, so say the answer is for the cells on an infinite plane
+                ui.label(format!("{} on an infinite plane", analysis));
+            }
+            
+            ui.separator();
+            
+            // Splits the cells into objects and counts them by name, also taken when a cycle is entered
+            if ui.button("📋 Census").on_hover_text("Counts the separate objects by name, like the ash of a soup search").clicked() {
+                self.simulation.apply(|world| world.take_census());
+            }
+            if snapshot.counting {
+                ui.spinner();
+            }
+            
+            ui.separator();
+            
+            ui.checkbox(&mut self.show_history, "📈 History");
+        });
+        
+        // Population, births, deaths and changed cells per generation, exported to a file per universe
+        if self.show_history {
+            self.history_chart(ui, &snapshot.history, &format!("life_history_{}.csv", index + 1));
+        }
+        
+        // Objects counted by name, from the last census
+        if let Some((generation, entries)) = &snapshot.census {
+            let objects: usize = entries.iter().map(|entry| entry.count).sum();
+            ui.collapsing(format!("Census at generation {}, {} objects", generation, objects), |ui| {
+                egui::Grid::new("census_grid").striped(true).show(ui, |ui| {
+                    for entry in entries {
+                        ui.label(&entry.name);
+                        ui.label(entry.count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+        
+        // Generations are not published until they finish, so keep redrawing to watch them fill in
+        if snapshot.progressive {
+            ui.ctx().request_repaint_after(Duration::from_millis(16));
+        }
+        
+        run_control
+    }
+}
+
+impl eframe::App for Universes {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Everything below draws one snapshot per universe, changes go back to their simulation threads as commands
+        let snapshots: Vec<_> = self.universes.iter().map(|universe| universe.simulation.snapshot()).collect();
+        
+        if self.universes.iter().any(|universe| universe.show_stats) {
+            egui::SidePanel::right("slice_stats").min_width(260.0).show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (index, (universe, snapshot)) in self.universes.iter().zip(&snapshots).enumerate() {
+                        if universe.show_stats {
+                            ui.push_id(index, |ui| {
+                                ui.strong(format!("Universe {}", index + 1));
+                                universe.stats_panel(ui, &snapshot.stats);
+                            });
+                            ui.separator();
+                        }
+                    }
+                });
+            });
+        }
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Async Conway's Game of Life (Row Coroutines)");
+            
+            // How many universes, and whether they start, pause and step together
             ui.horizontal(|ui| {
-                ui.label("Speed:");
-                let mut speed = 1000.0 / self.update_interval.as_millis() as f32;
-                if ui.add(egui::Slider::new(&mut speed, 0.5..=90.0).suffix(" gen/sec")).changed() {
-                    self.update_interval = Duration::from_millis((1000.0 / speed) as u64);
+                ui.label("Universes:");
+                let mut count = self.universes.len();
+                if ui.add(egui::DragValue::new(&mut count).clamp_range(1..=MAX_UNIVERSES)).changed() {
+                    self.set_count(count);
                 }
+                ui.checkbox(&mut self.synchronised, "Synchronised")
+                    .on_hover_text("Start, Pause and Step in any universe act on all of them");
                 
                 ui.separator();
                 
-                // Show current colors
-                ui.label("Live:");
-                ui.color_edit_button_srgba(&mut self.live_color);
-                ui.label("Dead:");
-                ui.color_edit_button_srgba(&mut self.dead_color);
+                if ui.button("▶ Start all").clicked() {
+                    self.run_all(RunControl::Start);
+                }
+                if ui.button("⏸ Pause all").clicked() {
+                    self.run_all(RunControl::Pause);
+                }
+                if ui.button("⏭ Step all").clicked() {
+                    self.run_all(RunControl::Step);
+                }
             });
             
-            ui.separator();
-            
             // Instructions - updated to mention async coroutines
-            ui.label("🚀 Each row runs as an async coroutine that yields cooperatively!");
+            ui.label("🚀 Each row runs as an async coroutine that yields cooperatively, every universe on the same runtime and scheduler!");
             ui.label("Click cells to toggle them alive/dead. Use Start/Pause to run the simulation.");
             
             ui.separator();
             
-            // Draw the grid (only show the active 50x50 area)
-            let box_size = 15.0;
-            let spacing = 0.5;
-            let grid_size = 50;  // Display size stays 50x50
-            
-            let start_pos = ui.cursor().min;
-            let total_size = Vec2::splat((box_size + spacing) * grid_size as f32 - spacing);
-            
-            let (response, painter) = ui.allocate_painter(total_size, egui::Sense::click());
-            
-            // Fill background
-            painter.rect_filled(
-                Rect::from_min_size(start_pos, total_size),
-                0.0,
-                Color32::BLACK,
-            );
-            
-            // Draw only the active area (grid[1..51][1..51])
-            for display_row in 0..grid_size {
-                for display_col in 0..grid_size {
-                    let grid_row = display_row + 1;  // Map to grid[1..51]
-                    let grid_col = display_col + 1;  // Map to grid[1..51]
-                    
-                    let x = start_pos.x + display_col as f32 * (box_size + spacing);
-                    let y = start_pos.y + display_row as f32 * (box_size + spacing);
-                    
-                    let rect = Rect::from_min_size(
-                        egui::pos2(x, y),
-                        Vec2::splat(box_size),
-                    );
-                    
-                    // Choose color based on cell state
-                    let cell_color = if self.grid[grid_row][grid_col] {
-                        self.live_color
-                    } else {
-                        self.dead_color
-                    };
-                    
-                    painter.rect_filled(rect, 1.0, cell_color);
-                    
-                    // Draw subtle border
-                    painter.rect_stroke(rect, 1.0, Stroke::new(0.2, Color32::from_gray(60)));
-                    
-                    // Handle clicking (only when not running) - use async version
-                    if !self.is_running && response.clicked() {
-                        if let Some(pos) = response.interact_pointer_pos() {
-                            if rect.contains(pos) {
-                                self.toggle_cell_async(grid_row, grid_col); // Use async version
-                            }
+            // Tiles as close to square as the count allows, each universe in its own frame
+            let columns = (self.universes.len() as f32).sqrt().ceil() as usize;
+            let tile_height = ui.available_height() / self.universes.len().div_ceil(columns) as f32;
+            let mut run_controls = Vec::new();
+            for (tile_row, universes) in self.universes.chunks_mut(columns).enumerate() {
+                ui.allocate_ui(Vec2::new(ui.available_width(), tile_height), |ui| {
+                    ui.columns(columns, |tiles| {
+                        for (tile_col, (universe, ui)) in universes.iter_mut().zip(tiles).enumerate() {
+                            let index = tile_row * columns + tile_col;
+                            ui.push_id(index, |ui| {
+                                egui::Frame::group(ui.style()).show(ui, |ui| {
+                                    if let Some(run_control) = universe.show(ui, index, &snapshots[index]) {
+                                        run_controls.push((index, run_control));
+                                    }
+                                });
+                            });
                         }
-                    }
-                }
+                    });
+                });
             }
             
-            ui.separator();
-            
-            // Statistics (count only the active area)
-            let live_cells: usize = (1..51).map(|row| 
-                (1..51).filter(|&col| self.grid[row][col]).count()
-            ).sum();
-            
-            ui.horizontal(|ui| {
-                ui.label(format!("Live cells: {}", live_cells));
-                ui.label(format!("Dead cells: {}", 2500 - live_cells));
-                ui.label(format!("Population: {:.1}%", (live_cells as f32 / 2500.0) * 100.0));
-            });
+            for (index, run_control) in run_controls {
+                self.run_control(index, run_control);
+            }
         });
-        
-        // Request repaint if running to keep animation smooth
-        if self.is_running {
-            ctx.request_repaint();
-        }
     }
 }
\ No newline at end of file
@@ -126,12 +126,10 @@ impl eframe::App for GridApp {
                     painter.rect_stroke(rect, 0.0, Stroke::new(0.3, Color32::GRAY));
                     
                     // Handle clicking
-                    if response.clicked() {
-                        if let Some(pos) = response.interact_pointer_pos() {
-                            if rect.contains(pos) {
-                                self.grid[row][col] = !self.grid[row][col];
-                            }
-                        }
+                    if response.clicked()
+                        && let Some(pos) = response.interact_pointer_pos()
+                        && rect.contains(pos) {
+                        self.grid[row][col] = !self.grid[row][col];
                     }
                 }
             }
@@ -0,0 +1,105 @@
+// bench_engines.rs - Times the bounded engines against each other on identical random seeds
+// cargo run --release -p life_core --example bench_engines [-- --size WIDTHxHEIGHT] [--generations N]
+// Without --size a range of square grids is run, the closure and coroutine engines sit out the
+// largest because they need a boxed closure per cell or a row vector per slice
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use life_core::bitpacked_engine::{BitGrid, BitPackedEngine};
+use life_core::closure_engine::ClosureEngine;
+use life_core::coroutine_engine::GenerationProcessor;
+use life_core::future_engine::FutureProcessor;
+use life_core::{grid, patterns, Boundary, GridEngine, Rule, TGrid};
+
+const SIZES: [usize; 4] = [64, 256, 1024, 4096];
+const SLOW_ENGINE_LIMIT: usize = 1024;     // Largest side the closure and coroutine engines are run on
+const SEED: u32 = 2024;
+
+fn arg_value(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+/// Runs `step` for `generations` and returns the time per generation
+fn time_per_generation(generations: u32, mut step: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..generations {
+        step();
+    }
+    start.elapsed() / generations.max(1)
+}
+
+fn report(engine: &str, per_generation: Duration, baseline: Option<Duration>) {
+    let speedup = baseline.map_or(String::new(), |baseline| format!("{:>9.1}x", baseline.as_secs_f64() / per_generation.as_secs_f64()));
+    println!("  {:<16} {:>12.3} ms/gen {}", engine, per_generation.as_secs_f64() * 1000.0, speedup);
+}
+
+fn bench(width: usize, height: usize, generations: u32) {
+    let rule = Rule::default();
+    let boundary = Boundary::Torus;
+    let mut seed = TGrid::new(width, height);
+    patterns::apply_random_pattern(&mut seed, SEED);
+
+    println!("{}x{}, {} generations, {} live at the start", width, height, generations, seed.count_live_cells());
+
+    // Bit-packed on its own representation, the result every other engine is checked against
+    let mut engine = BitPackedEngine::new(width, height, rule, boundary);
+    let mut bits = BitGrid::from_grid(&seed);
+    let bitpacked = time_per_generation(generations, || engine.step_bits(&mut bits));
+    let mut expected = TGrid::new(width, height);
+    bits.store(&mut expected);
+
+    if width.max(height) > SLOW_ENGINE_LIMIT {
+        report("bit-packed", bitpacked, None);
+        println!("  closure and coroutine engines skipped above {}", SLOW_ENGINE_LIMIT);
+        return;
+    }
+
+    let mut closure_engine = ClosureEngine::new(width, height, rule, boundary);
+    let mut grid = seed.clone();
+    let closures = time_per_generation(generations, || closure_engine.step(&mut grid));
+    assert_eq!(grid, expected, "closure engine disagrees with the bit-packed engine");
+
+    // Budget large enough that every row completes in its first slice
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let mut processor = GenerationProcessor::new(width, height, rule, boundary, Duration::from_secs(1));
+    let mut grid = Arc::new(seed.clone());
+    let coroutines = time_per_generation(generations, || {
+        grid = Arc::new(runtime.block_on(processor.process_generation(grid.clone())));
+    });
+    assert_eq!(*grid, expected, "coroutine engine disagrees with the bit-packed engine");
+
+    // Same coroutines split into one band of rows per core
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    processor.set_threads(threads);
+    let mut grid = Arc::new(seed.clone());
+    let banded = time_per_generation(generations, || {
+        grid = Arc::new(runtime.block_on(processor.process_generation(grid.clone())));
+    });
+    assert_eq!(*grid, expected, "banded coroutine engine disagrees with the bit-packed engine");
+
+    // The same rows as hand-written futures on the in-crate executor, head to head with tokio above
+    let mut future_engine = FutureProcessor::new(width, height, rule, boundary, Duration::from_secs(1));
+    let mut grid = seed.clone();
+    let futures = time_per_generation(generations, || future_engine.step(&mut grid));
+    assert_eq!(grid, expected, "future engine disagrees with the bit-packed engine");
+
+    report("closures", closures, None);
+    report("coroutines", coroutines, Some(closures));
+    report(&format!("coroutines x{}", threads), banded, Some(closures));
+    report("futures", futures, Some(closures));
+    report("bit-packed", bitpacked, Some(closures));
+}
+
+fn main() {
+    let generations = arg_value("--generations").and_then(|arg| arg.parse().ok()).unwrap_or(100);
+    let sizes: Vec<(usize, usize)> = match arg_value("--size").and_then(|arg| grid::parse_size(&arg)) {
+        Some(size) => vec![size],
+        None => SIZES.iter().map(|&size| (size, size)).collect(),
+    };
+
+    println!("Speedups are relative to the closure engine, all engines start from seed {}", SEED);
+    for (width, height) in sizes {
+        bench(width, height, generations);
+    }
+}
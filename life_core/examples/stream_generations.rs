@@ -0,0 +1,57 @@
+// stream_generations.rs - Reads generations from the async stream and the iterator instead of driving the engine
+// cargo run --release -p life_core --example stream_generations [-- --size WIDTHxHEIGHT] [--generations N]
+// Both stop early if the random soup settles into a cycle
+
+use std::time::Duration;
+
+use tokio_stream::StreamExt;
+
+use life_core::closure_engine::ClosureEngine;
+use life_core::coroutine_engine::GenerationProcessor;
+use life_core::stream::{GenerationStream, Generations};
+use life_core::{grid, patterns, Boundary, Rule, TGrid};
+
+const SEED: u32 = 7;
+
+fn arg_value(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+#[tokio::main]
+async fn main() {
+    let (width, height) = grid::size_from_args();
+    let generations = arg_value("--generations").and_then(|value| value.parse().ok()).unwrap_or(500);
+    let (rule, boundary) = (Rule::default(), Boundary::default());
+    let mut start = TGrid::new(width, height);
+    patterns::apply_random_pattern(&mut start, SEED);
+
+    // Async: every generation is computed only when it is awaited
+    let processor = GenerationProcessor::new(width, height, rule, boundary, Duration::from_micros(50));
+    let mut stream = GenerationStream::new(processor, start.clone()).stop_on_cycle();
+    while let Some(snapshot) = stream.next().await {
+        if snapshot.generation % 100 == 0 || snapshot.cycle.is_some() {
+            println!("stream   gen {:>5}: {:>5} live{}", snapshot.generation, snapshot.grid.count_live_cells(), snapshot.cycle.map_or(String::new(), |cycle| format!(", {}", cycle)));
+        }
+        if snapshot.generation == generations { break; }
+    }
+
+    // Stream combinators, skipping the first 10 generations and taking the next 5
+    let processor = GenerationProcessor::new(width, height, rule, boundary, Duration::from_micros(50));
+    let populations: Vec<usize> = GenerationStream::new(processor, start.clone())
+        .skip(10)
+        .take(5)
+        .map(|snapshot| snapshot.grid.count_live_cells())
+        .collect()
+        .await;
+    println!("stream   gens 11..=15: {:?}", populations);
+
+    // The same run from a synchronous iterator over any bounded engine
+    let engine = Box::new(ClosureEngine::new(width, height, rule, boundary));
+    let last = Generations::new(engine, start)
+        .stop_on_cycle()
+        .take(generations as usize)
+        .last();
+    if let Some(snapshot) = last {
+        println!("iterator gen {:>5}: {:>5} live{}", snapshot.generation, snapshot.grid.count_live_cells(), snapshot.cycle.map_or(String::new(), |cycle| format!(", {}", cycle)));
+    }
+}
@@ -0,0 +1,161 @@
+// age.rs - How long each cell of the active area has been alive or dead, and how often it changed lately
+// Tracked alongside the engines by comparing each new generation with the last, for the age, heat and trail renderings
+
+use crate::grid::TGrid;
+
+/// Most generations the heat of a cell can look back over, one bit each
+pub const MAX_HEAT_WINDOW: u32 = u32::BITS;
+
+/// The recent life of one cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellAge {
+    pub alive   : bool,
+    pub lived   : bool,      // Has been alive since tracking began
+    pub age     : u16,       // Generations in its current state, alive or dead, saturating
+    changes     : u32,       // One bit per generation, newest lowest, set where the cell changed state
+}
+
+impl CellAge {
+    /// Generations the cell has been alive, `None` while dead
+    pub fn alive_for(&self) -> Option<u16> {
+        self.alive.then_some(self.age)
+    }
+
+    /// Generations since the cell died, `None` while alive or if it never lived
+    pub fn dead_for(&self) -> Option<u16> {
+        (!self.alive && self.lived).then_some(self.age)
+    }
+
+    /// Times the cell changed state over the last `window` generations, at most `MAX_HEAT_WINDOW`
+    pub fn heat(&self, window: u32) -> u32 {
+        let mask = u32::MAX.checked_shr(MAX_HEAT_WINDOW - window.min(MAX_HEAT_WINDOW)).unwrap_or(0);
+        (self.changes & mask).count_ones()
+    }
+}
+
+/// Ages of every cell in the active area of a grid, row by row
+#[derive(Clone, Debug, Default)]
+pub struct CellAges {
+    width  : usize,
+    height : usize,
+    cells  : Vec<CellAge>,
+}
+
+impl CellAges {
+    /// Starts tracking from `grid`, every live cell one generation old
+    pub fn new(grid: &TGrid) -> Self {
+        let mut ages = Self::default();
+        ages.reset(grid);
+        ages
+    }
+
+    /// Forgets everything and starts again from `grid`, after edits or a resize
+    pub fn reset(&mut self, grid: &TGrid) {
+        self.width = grid.width();
+        self.height = grid.height();
+        self.cells = grid.rows()
+            .flat_map(|row| grid.cols().map(move |col| grid[row][col]))
+            .map(|alive| CellAge { alive, lived: alive, age: 1, changes: 0 })
+            .collect();
+    }
+
+    /// Ages every cell by one generation, `grid` is the generation just computed
+    /// A grid of another size starts the tracking over
+    pub fn update(&mut self, grid: &TGrid) {
+        if (grid.width(), grid.height()) != (self.width, self.height) {
+            self.reset(grid);
+            return;
+        }
+
+        let cells = grid.rows().flat_map(|row| grid.cols().map(move |col| grid[row][col]));
+        for (cell, alive) in self.cells.iter_mut().zip(cells) {
+            cell.changes <<= 1;
+            if alive == cell.alive {
+                cell.age = cell.age.saturating_add(1);
+            } else {
+                *cell = CellAge { alive, lived: true, age: 1, changes: cell.changes | 1 };
+            }
+        }
+    }
+
+    /// The cell shown at a display position, (0, 0) being the top-left of the active area
+    pub fn get(&self, display_row: usize, display_col: usize) -> CellAge {
+        self.cells.get(display_row * self.width + display_col).copied().unwrap_or_default()
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GRID_START;
+
+    /// A 3x3 grid with only its top-left active cell set as given
+    fn corner(alive: bool) -> TGrid {
+        let mut grid = TGrid::new(3, 3);
+        grid[GRID_START][GRID_START] = alive;
+        grid
+    }
+
+    #[test]
+    fn tracking_starts_every_cell_one_generation_old() {
+        let ages = CellAges::new(&corner(true));
+        assert_eq!(ages.get(0, 0).alive_for(), Some(1));
+        assert_eq!(ages.get(0, 1).alive_for(), None);
+        assert_eq!(ages.get(0, 1).dead_for(), None, "a cell that never lived has no trail");
+    }
+
+    #[test]
+    fn age_counts_generations_in_the_current_state() {
+        let mut ages = CellAges::new(&corner(true));
+        for _ in 0..4 {
+            ages.update(&corner(true));
+        }
+        assert_eq!(ages.get(0, 0).alive_for(), Some(5));
+        assert_eq!(ages.get(0, 2).age, 5);
+
+        ages.update(&corner(false));
+        assert_eq!(ages.get(0, 0).alive_for(), None);
+        assert_eq!(ages.get(0, 0).dead_for(), Some(1));
+        ages.update(&corner(false));
+        assert_eq!(ages.get(0, 0).dead_for(), Some(2));
+
+        ages.update(&corner(true));
+        assert_eq!(ages.get(0, 0).alive_for(), Some(1));
+    }
+
+    #[test]
+    fn heat_counts_changes_within_the_window() {
+        let mut ages = CellAges::new(&corner(false));
+        for generation in 1..=40 {
+            ages.update(&corner(generation % 2 == 1));
+        }
+        let cell = ages.get(0, 0);
+        assert_eq!(cell.heat(0), 0);
+        assert_eq!(cell.heat(4), 4);
+        assert_eq!(cell.heat(MAX_HEAT_WINDOW), MAX_HEAT_WINDOW);
+        assert_eq!(cell.heat(MAX_HEAT_WINDOW + 10), MAX_HEAT_WINDOW, "windows past the bits kept are clamped");
+        assert_eq!(ages.get(1, 1).heat(MAX_HEAT_WINDOW), 0);
+
+        // Changes age out of a short window once the cell settles
+        for _ in 0..3 {
+            ages.update(&corner(false));
+        }
+        assert_eq!(ages.get(0, 0).heat(4), 1);
+        assert_eq!(ages.get(0, 0).heat(3), 0);
+    }
+
+    #[test]
+    fn grid_of_another_size_starts_over() {
+        let mut ages = CellAges::new(&corner(true));
+        ages.update(&corner(true));
+
+        let mut bigger = TGrid::new(5, 4);
+        bigger[GRID_START][GRID_START] = true;
+        ages.update(&bigger);
+        assert_eq!((ages.width(), ages.height()), (5, 4));
+        assert_eq!(ages.get(0, 0).alive_for(), Some(1));
+    }
+}
@@ -0,0 +1,374 @@
+// bitpacked_engine.rs - Bounded engine packing 64 cells into each u64 word
+// Neighbour counts come from full adders over whole words, so a generation costs a few
+// dozen bit operations per 64 cells instead of one closure call per cell
+
+use crate::grid::TGrid;
+use crate::rules::Rule;
+use crate::topology::Boundary;
+use crate::GridEngine;
+
+const WORD_BITS: usize = 64;
+
+/// Grid of bits laid out like `TGrid`: rows `1..=height` and columns `1..=width` are active,
+/// row and column 0 and the last row and column form a dead border
+/// Column `col` of a row is bit `col % 64` of word `col / 64`
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitGrid {
+    width         : usize,
+    height        : usize,
+    words_per_row : usize,
+    words         : Vec<u64>,   // (height + 2) rows of words_per_row words
+}
+
+impl BitGrid {
+    /// An all-dead grid with a `width` x `height` active area
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "grid must be at least 1x1, got {}x{}", width, height);
+        let words_per_row = (width + 2).div_ceil(WORD_BITS);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * (height + 2)],
+        }
+    }
+
+    /// Packs the cells of `grid`
+    pub fn from_grid(grid: &TGrid) -> Self {
+        let mut bits = Self::new(grid.width(), grid.height());
+        bits.load(grid);
+        bits
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    /// Words of row `row`, border row or not
+    pub fn row(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    fn row_mut(&mut self, row: usize) -> &mut [u64] {
+        &mut self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.row(row)[col / WORD_BITS] >> (col % WORD_BITS) & 1 == 1
+    }
+
+    /// Sets an active cell, coordinates outside the active area are ignored so the border stays dead
+    pub fn set(&mut self, row: usize, col: usize, alive: bool) {
+        if !(1..=self.height).contains(&row) || !(1..=self.width).contains(&col) { return; }
+        let bit = 1 << (col % WORD_BITS);
+        let word = &mut self.row_mut(row)[col / WORD_BITS];
+        if alive { *word |= bit; } else { *word &= !bit; }
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Copies the cells of `grid`, resizing to match it
+    pub fn load(&mut self, grid: &TGrid) {
+        if (self.width, self.height) != (grid.width(), grid.height()) {
+            *self = Self::new(grid.width(), grid.height());
+        }
+        for row in grid.rows() {
+            let words = &mut self.words[row * self.words_per_row..(row + 1) * self.words_per_row];
+            words.fill(0);
+            for col in grid.cols() {
+                words[col / WORD_BITS] |= (grid[row][col] as u64) << (col % WORD_BITS);
+            }
+        }
+    }
+
+    /// Unpacks into the active area of `grid`, which must be the same size
+    pub fn store(&self, grid: &mut TGrid) {
+        assert_eq!((grid.width(), grid.height()), (self.width, self.height));
+        for row in grid.rows() {
+            let words = self.row(row);
+            for col in grid.cols() {
+                grid[row][col] = words[col / WORD_BITS] >> (col % WORD_BITS) & 1 == 1;
+            }
+        }
+    }
+
+    /// Number of live cells in the active area
+    pub fn count_live_cells(&self) -> usize {
+        (1..=self.height)
+            .map(|row| self.row(row).iter().map(|word| word.count_ones() as usize).sum::<usize>())
+            .sum()
+    }
+}
+
+/// Adds three bit planes, returning (sum, carry) for each of the 64 lanes
+#[inline]
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let partial = a ^ b;
+    (partial ^ c, (a & b) | (partial & c))
+}
+
+/// Steps a `BitGrid`, or a `TGrid` by packing it first
+pub struct BitPackedEngine {
+    width    : usize,
+    height   : usize,
+    rule     : Rule,
+    boundary : Boundary,
+    outcomes : [(bool, bool); 9],   // (birth, survival) for each neighbour count
+    active   : Vec<u64>,            // Mask of the active columns in a row
+    current  : BitGrid,             // Packed copy used when stepping a TGrid
+    next     : BitGrid,
+    above    : Vec<u64>,            // Neighbour rows with the wrapped columns 0 and width + 1 filled in
+    middle   : Vec<u64>,
+    below    : Vec<u64>,
+    flipped  : [Vec<u64>; 2],       // First and last rows mirrored, read across a cross-surface edge
+}
+
+impl BitPackedEngine {
+    pub fn new(width: usize, height: usize, rule: Rule, boundary: Boundary) -> Self {
+        let next = BitGrid::new(width, height);
+        let words_per_row = next.words_per_row;
+
+        let mut active = vec![0; words_per_row];
+        for col in 1..=width {
+            active[col / WORD_BITS] |= 1 << (col % WORD_BITS);
+        }
+
+        Self {
+            width,
+            height,
+            rule,
+            boundary,
+            outcomes : Self::outcomes(rule),
+            active,
+            current  : next.clone(),
+            next,
+            above    : vec![0; words_per_row],
+            middle   : vec![0; words_per_row],
+            below    : vec![0; words_per_row],
+            flipped  : [vec![0; words_per_row], vec![0; words_per_row]],
+        }
+    }
+
+    fn outcomes(rule: Rule) -> [(bool, bool); 9] {
+        std::array::from_fn(|count| (rule.next_state(false, count as u8), rule.next_state(true, count as u8)))
+    }
+
+    /// Replaces `bits` with its next generation
+    pub fn step_bits(&mut self, bits: &mut BitGrid) {
+        assert_eq!((bits.width, bits.height), (self.width, self.height));
+
+        if self.boundary == Boundary::CrossSurface {
+            self.mirror_edge_rows(bits);
+        }
+
+        self.fill_row(bits, 0, Row::Middle);
+        self.fill_row(bits, 1, Row::Below);
+        for row in 1..=self.height {
+            // Slide the three-row window down one row
+            std::mem::swap(&mut self.above, &mut self.middle);
+            std::mem::swap(&mut self.middle, &mut self.below);
+            self.fill_row(bits, row + 1, Row::Below);
+
+            let words = self.active.len();
+            for i in 0..words {
+                // Each neighbour as a whole word, bit `col` holding that neighbour of column `col`
+                let left = |row: &[u64]| row[i] << 1 | if i > 0 { row[i - 1] >> 63 } else { 0 };
+                let right = |row: &[u64]| row[i] >> 1 | if i + 1 < words { row[i + 1] << 63 } else { 0 };
+
+                // Full-adder tree summing the eight neighbours into a 4-bit count per lane
+                let (sum_above, carry_above) = full_add(left(&self.above), self.above[i], right(&self.above));
+                let (sum_below, carry_below) = full_add(left(&self.below), self.below[i], right(&self.below));
+                let (sum_sides, carry_sides) = (left(&self.middle) ^ right(&self.middle), left(&self.middle) & right(&self.middle));
+                let (ones, carry_ones) = full_add(sum_above, sum_below, sum_sides);
+                let (partial_twos, carry_twos) = full_add(carry_above, carry_below, carry_sides);
+                let (twos, carry_partial) = (partial_twos ^ carry_ones, partial_twos & carry_ones);
+                let (fours, eights) = (carry_twos ^ carry_partial, carry_twos & carry_partial);
+
+                // Pick out the lanes whose count the rule keeps alive or brings to life
+                let alive = self.middle[i];
+                let mut next = 0;
+                for (count, &(birth, survival)) in self.outcomes.iter().enumerate() {
+                    if !birth && !survival { continue; }
+                    let bit = |plane: u64, weight: usize| if count & weight != 0 { plane } else { !plane };
+                    let matches = bit(ones, 1) & bit(twos, 2) & bit(fours, 4) & bit(eights, 8);
+                    next |= matches & match (birth, survival) {
+                        (true, true)  => !0,
+                        (true, false) => !alive,
+                        _             => alive,
+                    };
+                }
+                self.next.row_mut(row)[i] = next & self.active[i];
+            }
+        }
+
+        std::mem::swap(bits, &mut self.next);
+    }
+
+    /// Copies neighbour row `row`, which may be a border row, into a window row,
+    /// mapping it and the wrapped columns on either side through the boundary
+    fn fill_row(&mut self, bits: &BitGrid, row: usize, target: Row) {
+        let (width, height) = (self.width, self.height);
+        let mut words = std::mem::take(match target {
+            Row::Middle => &mut self.middle,
+            Row::Below  => &mut self.below,
+        });
+
+        let source = if (1..=height).contains(&row) {
+            bits.row(row)
+        } else {
+            match self.boundary {
+                Boundary::DeadBorder => bits.row(row),
+                Boundary::Torus | Boundary::KleinBottle => bits.row(if row == 0 { height } else { 1 }),
+                Boundary::CrossSurface => &self.flipped[if row == 0 { 1 } else { 0 }],
+            }
+        };
+        words.copy_from_slice(source);
+
+        for col in [0, width + 1] {
+            let (source_row, source_col) = self.boundary.wrap(row, col, width, height);
+            words[col / WORD_BITS] |= (bits.get(source_row, source_col) as u64) << (col % WORD_BITS);
+        }
+
+        match target {
+            Row::Middle => self.middle = words,
+            Row::Below  => self.below = words,
+        }
+    }
+
+    /// Mirrors the first and last active rows left to right
+    fn mirror_edge_rows(&mut self, bits: &BitGrid) {
+        for (flipped, row) in self.flipped.iter_mut().zip([1, self.height]) {
+            flipped.fill(0);
+            for col in 1..=self.width {
+                if bits.get(row, col) {
+                    let mirrored = self.width + 1 - col;
+                    flipped[mirrored / WORD_BITS] |= 1 << (mirrored % WORD_BITS);
+                }
+            }
+        }
+    }
+}
+
+/// Which row of the three-row window `fill_row` writes
+#[derive(Clone, Copy)]
+enum Row {
+    Middle,
+    Below,
+}
+
+impl GridEngine for BitPackedEngine {
+    /// Packs `grid`, steps it and unpacks the next generation back into it
+    fn step(&mut self, grid: &mut TGrid) {
+        let mut current = std::mem::replace(&mut self.current, BitGrid::new(1, 1));
+        current.load(grid);
+        self.step_bits(&mut current);
+        current.store(grid);
+        self.current = current;
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        *self = Self::new(width, height, self.rule, self.boundary);
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.outcomes = Self::outcomes(rule);
+    }
+
+    fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::closure_engine::ClosureEngine;
+    use crate::patterns;
+    use crate::rules::RULES;
+    use crate::test_support;
+    use crate::topology::BOUNDARIES;
+
+    /// Checks a bit-packed engine set up for the soup against the closure engine
+    fn assert_matches_closure_engine(width: usize, height: usize, rule: Rule, boundary: Boundary, seed: u32) {
+        let mut bits = BitPackedEngine::new(width, height, rule, boundary);
+        test_support::assert_matches_closure_engine(&mut bits, width, height, rule, boundary, seed, 12);
+    }
+
+    #[test]
+    fn matches_the_closure_engine_across_word_edges() {
+        // Word boundaries fall inside the row, right after it, and exactly on the border column
+        for width in [1, 2, 61, 62, 63, 64, 65, 127, 128, 130] {
+            for boundary in [Boundary::DeadBorder, Boundary::Torus, Boundary::KleinBottle] {
+                assert_matches_closure_engine(width, 9, Rule::default(), boundary, width as u32);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_closure_engine_on_a_cross_surface() {
+        for width in [63, 65, 130] {
+            assert_matches_closure_engine(width, 11, Rule::default(), Boundary::CrossSurface, width as u32);
+        }
+    }
+
+    #[test]
+    fn matches_the_closure_engine_for_every_rule() {
+        for named in RULES {
+            for &boundary in BOUNDARIES {
+                assert_matches_closure_engine(65, 20, named.rule, boundary, 17);
+            }
+        }
+    }
+
+    #[test]
+    fn single_rows_and_columns_wrap_onto_themselves() {
+        for &boundary in BOUNDARIES {
+            assert_matches_closure_engine(130, 1, Rule::default(), boundary, 3);
+            assert_matches_closure_engine(1, 40, Rule::default(), boundary, 4);
+        }
+    }
+
+    #[test]
+    fn glider_crosses_the_word_edge_on_a_torus() {
+        // A glider placed against column 63 so every step has it straddling the first and second words
+        let mut grid = TGrid::new(70, 8);
+        for (row, col) in [(1, 62), (2, 63), (3, 61), (3, 62), (3, 63)] {
+            grid[row][col] = true;
+        }
+        let mut expected = grid.clone();
+
+        let mut closures = ClosureEngine::new(70, 8, Rule::default(), Boundary::Torus);
+        let mut bits = BitPackedEngine::new(70, 8, Rule::default(), Boundary::Torus);
+        for generation in 1..=40 {
+            closures.step(&mut expected);
+            bits.step(&mut grid);
+            assert_eq!(grid, expected, "generation {}", generation);
+        }
+        assert_eq!(grid.count_live_cells(), 5);
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        let mut grid = TGrid::new(130, 5);
+        patterns::apply_random_pattern(&mut grid, 8);
+        let bits = BitGrid::from_grid(&grid);
+        assert_eq!(bits.count_live_cells(), grid.count_live_cells());
+
+        let mut unpacked = TGrid::new(130, 5);
+        bits.store(&mut unpacked);
+        assert_eq!(unpacked, grid);
+    }
+
+    #[test]
+    fn set_ignores_the_border() {
+        let mut bits = BitGrid::new(64, 3);
+        bits.set(0, 5, true);
+        bits.set(2, 0, true);
+        bits.set(2, 65, true);
+        bits.set(2, 64, true);
+        assert_eq!(bits.count_live_cells(), 1);
+        assert!(bits.get(2, 64));
+    }
+}
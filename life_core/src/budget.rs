@@ -0,0 +1,247 @@
+// budget.rs - Feedback control of the per-slice time budget
+// Measures how long each generation really took and resizes the slices so generations land just inside a frame deadline
+// Longer slices mean fewer of them and less overhead, shorter ones interleave the rows more finely
+
+use std::time::Duration;
+
+/// Shortest budget the controller sets, a slice still computes at least one cell
+pub const MIN_BUDGET: Duration = Duration::from_micros(1);
+
+/// Generations finishing between this fraction of the target and the target leave the budget alone
+const HOLD_BAND: f64 = 0.8;
+
+/// Weight of the newest generation in the smoothed latency
+const SMOOTHING: f64 = 0.3;
+
+/// Most the budget grows or shrinks by after one generation
+const MAX_STEP: f64 = 2.0;
+
+/// What the controller did after the last generation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControllerState {
+    #[default]
+    Waiting,        // Nothing measured since the target was set
+    Growing,        // Late, slices get longer
+    Shrinking,      // Early, slices get shorter
+    Holding,        // Inside the band below the target
+    AtFloor,        // Early even with the shortest slices
+    AtCeiling,      // Late even with slices as long as the whole deadline
+}
+
+impl ControllerState {
+    pub fn name(self) -> &'static str {
+        match self {
+            ControllerState::Waiting   => "Waiting for a generation",
+            ControllerState::Growing   => "Growing slices",
+            ControllerState::Shrinking => "Shrinking slices",
+            ControllerState::Holding   => "On target",
+            ControllerState::AtFloor   => "Shortest slices, still early",
+            ControllerState::AtCeiling => "Longest slices, still late",
+        }
+    }
+}
+
+/// Adjusts the per-slice budget from measured generation latencies, in whole microseconds
+#[derive(Clone, Copy, Debug)]
+pub struct BudgetController {
+    target   : Duration,              // Frame deadline a generation should finish within
+    budget   : Duration,              // Per-slice budget for the next generation
+    latency  : Option<Duration>,      // Smoothed generation latency
+    last     : Option<Duration>,      // Latency of the last generation as measured
+    state    : ControllerState,
+    rows     : usize,                 // Active rows the initial budget was spread over
+}
+
+impl BudgetController {
+    /// Starts from ten slices per row if a generation took the whole deadline, `target / (rows * 10)`
+    pub fn new(target: Duration, rows: usize) -> Self {
+        let budget = Duration::from_micros((target.as_micros() as u64 / (rows.max(1) as u64 * 10)).max(1));
+        Self { target, budget, latency: None, last: None, state: ControllerState::Waiting, rows }
+    }
+
+    /// Starts over from the initial budget when the deadline or the number of rows changes
+    pub fn set_target(&mut self, target: Duration, rows: usize) {
+        if target != self.target || rows != self.rows {
+            *self = Self::new(target, rows);
+        }
+    }
+
+    /// Feeds in how long a completed generation took and adjusts the budget for the next one
+    pub fn record(&mut self, latency: Duration) {
+        let smoothed = match self.latency {
+            Some(previous) => previous.mul_f64(1.0 - SMOOTHING) + latency.mul_f64(SMOOTHING),
+            None => latency,
+        };
+        self.latency = Some(smoothed);
+        self.last = Some(latency);
+
+        let ratio = smoothed.as_secs_f64() / self.target.as_secs_f64().max(f64::EPSILON);
+        let micros = self.budget.as_micros() as f64;
+        let (budget, state) = if ratio > 1.0 {
+            ((micros * ratio.min(MAX_STEP)).ceil(), ControllerState::Growing)
+        } else if ratio < HOLD_BAND {
+            ((micros * (ratio / HOLD_BAND).max(1.0 / MAX_STEP)).floor(), ControllerState::Shrinking)
+        } else {
+            (micros, ControllerState::Holding)
+        };
+
+        let ceiling = self.target.max(MIN_BUDGET);
+        self.budget = Duration::from_micros(budget as u64).clamp(MIN_BUDGET, ceiling);
+        self.state = match state {
+            ControllerState::Growing if self.budget == ceiling => ControllerState::AtCeiling,
+            ControllerState::Shrinking if self.budget == MIN_BUDGET => ControllerState::AtFloor,
+            state => state,
+        };
+    }
+
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.last
+    }
+
+    pub fn state(&self) -> ControllerState {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn ms(millis: u64) -> Duration { Duration::from_millis(millis) }
+    const fn us(micros: u64) -> Duration { Duration::from_micros(micros) }
+
+    /// 10ms deadline over 10 rows starts at 100us per slice
+    fn controller() -> BudgetController {
+        BudgetController::new(ms(10), 10)
+    }
+
+    #[test]
+    fn starts_at_ten_slices_per_row_and_waits() {
+        let controller = controller();
+        assert_eq!(controller.budget(), us(100));
+        assert_eq!(controller.state(), ControllerState::Waiting);
+        assert_eq!((controller.latency(), controller.last()), (None, None));
+    }
+
+    #[test]
+    fn late_generations_grow_the_budget() {
+        let mut controller = controller();
+        controller.record(ms(15));
+        assert_eq!(controller.budget(), us(150));
+        assert_eq!(controller.state(), ControllerState::Growing);
+    }
+
+    #[test]
+    fn growth_is_limited_per_generation() {
+        let mut controller = controller();
+        controller.record(ms(100));
+        assert_eq!(controller.budget(), us(200));
+    }
+
+    #[test]
+    fn early_generations_shrink_the_budget() {
+        let mut controller = controller();
+        controller.record(ms(6));
+        // Three quarters of it, rounded down from whatever the float division gives
+        assert!((us(74)..=us(75)).contains(&controller.budget()), "{:?}", controller.budget());
+        assert_eq!(controller.state(), ControllerState::Shrinking);
+
+        // However early, one generation at most halves it
+        let mut controller = BudgetController::new(ms(10), 10);
+        controller.record(Duration::ZERO);
+        assert_eq!(controller.budget(), us(50));
+    }
+
+    #[test]
+    fn generations_just_inside_the_deadline_hold_the_budget() {
+        let mut controller = controller();
+        for latency in [ms(8), ms(9), ms(10)] {
+            controller.record(latency);
+            assert_eq!(controller.budget(), us(100));
+            assert_eq!(controller.state(), ControllerState::Holding);
+        }
+    }
+
+    #[test]
+    fn latency_is_smoothed() {
+        let mut controller = controller();
+        controller.record(ms(10));
+        controller.record(ms(20));
+        assert_eq!(controller.last(), Some(ms(20)));
+        assert_eq!(controller.latency(), Some(ms(13)));
+        assert_eq!(controller.budget(), us(130));
+    }
+
+    #[test]
+    fn budget_stops_at_the_deadline() {
+        let mut controller = BudgetController::new(ms(1), 1);
+        let mut budgets = Vec::new();
+        for _ in 0..5 {
+            controller.record(ms(100));
+            budgets.push(controller.budget().as_micros());
+        }
+        assert_eq!(budgets, vec![200, 400, 800, 1000, 1000]);
+        assert_eq!(controller.state(), ControllerState::AtCeiling);
+    }
+
+    #[test]
+    fn budget_stops_at_the_floor() {
+        let mut controller = BudgetController::new(ms(10), 10_000);
+        assert_eq!(controller.budget(), MIN_BUDGET);
+        controller.record(Duration::ZERO);
+        assert_eq!(controller.budget(), MIN_BUDGET);
+        assert_eq!(controller.state(), ControllerState::AtFloor);
+    }
+
+    #[test]
+    fn leaves_the_limits_once_the_latency_recovers() {
+        let mut controller = BudgetController::new(ms(1), 1);
+        for _ in 0..5 {
+            controller.record(ms(100));
+        }
+        assert_eq!(controller.state(), ControllerState::AtCeiling);
+
+        // The smoothed latency takes a few generations to fall back below the target
+        let states: Vec<_> = (0..20).map(|_| { controller.record(Duration::ZERO); controller.state() }).collect();
+        assert!(states.contains(&ControllerState::Shrinking));
+        assert!(controller.budget() < ms(1));
+    }
+
+    #[test]
+    fn new_target_starts_over() {
+        let mut controller = controller();
+        controller.record(ms(15));
+
+        controller.set_target(ms(10), 10);
+        assert_eq!(controller.state(), ControllerState::Growing);
+
+        controller.set_target(ms(20), 10);
+        assert_eq!(controller.budget(), us(200));
+        assert_eq!(controller.state(), ControllerState::Waiting);
+        assert_eq!(controller.latency(), None);
+    }
+
+    #[test]
+    fn resized_grid_starts_over() {
+        let mut controller = controller();
+        controller.record(ms(15));
+
+        // Same deadline over four times the rows
+        controller.set_target(ms(10), 40);
+        assert_eq!(controller.budget(), us(25));
+        assert_eq!(controller.state(), ControllerState::Waiting);
+        assert_eq!(controller.latency(), None);
+    }
+}
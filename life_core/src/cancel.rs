@@ -0,0 +1,27 @@
+// cancel.rs - Cancelling work that is already under way
+// A token is shared between whoever may cancel and the work that checks it between steps
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared flag asking the work holding a clone to stop at its next check
+/// Stays cancelled until `reset`, so a cancel sent just before the work starts is not lost
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Lets the next piece of work run, call it once whatever asked for the cancel has been dealt with
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Release);
+    }
+}
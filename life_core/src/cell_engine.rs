@@ -0,0 +1,293 @@
+// cell_engine.rs - One resumable coroutine per cell, yielding after every neighbour it counts
+// The closure engine's lambda per cell, made cooperative: a scheduler interleaves the cells
+// Cells are grouped into tasks of one cell, one row or one block, and the scheduler picks a task per step
+
+use std::time::Instant;
+
+use crate::grid::TGrid;
+use crate::rules::Rule;
+use crate::scheduler::{RowInfo, SchedulingPolicy};
+use crate::topology::Boundary;
+use crate::GridEngine;
+
+/// Side of the square blocks used by `Granularity::Block`
+pub const BLOCK_SIZE: usize = 8;
+
+/// What a resume of a cell coroutine produced
+pub enum Resume {
+    Yielded,             // Counted one more neighbour
+    Complete(bool),      // All eight counted, this is the cell's next state
+}
+
+/// One cell's next state as a generator, with baked-in coordinates and neighbour positions
+/// The first eight resumes each count one neighbour and yield, the ninth applies the rule
+#[derive(Clone, Copy)]
+pub struct CellCoroutine {
+    row       : usize,
+    col       : usize,
+    neighbors : [(usize, usize); 8],
+    next      : usize,    // Neighbour to count on the next resume, 8 once all are counted
+    count     : u8,
+}
+
+impl CellCoroutine {
+    pub fn new(row: usize, col: usize, neighbors: [(usize, usize); 8]) -> Self {
+        Self { row, col, neighbors, next: 0, count: 0 }
+    }
+
+    /// Rewinds to the first neighbour for a new generation
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.count = 0;
+    }
+
+    pub fn resume(&mut self, grid: &TGrid, rule: Rule) -> Resume {
+        match self.neighbors.get(self.next) {
+            Some(&(nr, nc)) => {
+                if grid[nr][nc] { self.count += 1; }
+                self.next += 1;
+                Resume::Yielded
+            }
+            None => Resume::Complete(rule.next_state(grid[self.row][self.col], self.count)),
+        }
+    }
+}
+
+/// How many cells one scheduled task steps through, one resume at a time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Granularity {
+    Cell,           // Every cell is its own task
+    #[default]
+    Row,            // A task per row, its cells run one after the other
+    Block,          // A task per BLOCK_SIZE x BLOCK_SIZE block
+}
+
+pub const GRANULARITIES: &[Granularity] = &[Granularity::Cell, Granularity::Row, Granularity::Block];
+
+impl Granularity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Granularity::Cell  => "Cell",
+            Granularity::Row   => "Row",
+            Granularity::Block => "Block",
+        }
+    }
+}
+
+/// How the cell engine groups and orders its coroutines
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellSchedule {
+    pub granularity : Granularity,
+    pub policy      : SchedulingPolicy,
+}
+
+/// A group of cell coroutines run in order, each step resumes the current one once
+struct CellTask {
+    cells    : Vec<usize>,     // Indices into the engine's coroutines
+    position : usize,          // Cell being resumed, all before it are complete
+}
+
+/// Bounded engine whose cells are coroutines interleaved by a scheduler
+pub struct CellEngine {
+    cells        : Vec<CellCoroutine>,
+    tasks        : Vec<CellTask>,
+    next_grid    : TGrid,
+    rule         : Rule,
+    boundary     : Boundary,
+    schedule     : CellSchedule,
+    last_resumes : usize,          // Resumes the last step took, nine per cell
+}
+
+impl CellEngine {
+    pub fn new(width: usize, height: usize, rule: Rule, boundary: Boundary, schedule: CellSchedule) -> Self {
+        let next_grid = TGrid::new(width, height);
+
+        let mut cells = Vec::with_capacity(next_grid.active_cells());
+        for grid_row in next_grid.rows() {
+            for grid_col in next_grid.cols() {
+                cells.push(CellCoroutine::new(grid_row, grid_col, boundary.neighbors(grid_row, grid_col, width, height)));
+            }
+        }
+
+        Self {
+            tasks: group_cells(width, height, schedule.granularity),
+            cells,
+            next_grid,
+            rule,
+            boundary,
+            schedule,
+            last_resumes: 0,
+        }
+    }
+
+    pub fn schedule(&self) -> CellSchedule {
+        self.schedule
+    }
+
+    pub fn set_schedule(&mut self, schedule: CellSchedule) {
+        if schedule.granularity != self.schedule.granularity {
+            self.tasks = group_cells(self.next_grid.width(), self.next_grid.height(), schedule.granularity);
+        }
+        self.schedule = schedule;
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// What the scheduler is told about each task
+    /// Tasks are numbered top to bottom, so equal deadlines already give earliest-deadline-first that order
+    fn task_infos(&self, grid: &TGrid) -> Vec<RowInfo> {
+        let deadline = Instant::now();
+        self.tasks.iter()
+            .map(|task| {
+                let first = &self.cells[task.cells[0]];
+                let live_cells = task.cells.iter()
+                    .map(|&i| {
+                        let cell = &self.cells[i];
+                        cell.neighbors.iter().filter(|&&(nr, nc)| grid[nr][nc]).count() + grid[cell.row][cell.col] as usize
+                    })
+                    .sum();
+                RowInfo { row: first.row, live_cells, deadline }
+            })
+            .collect()
+    }
+}
+
+/// Splits the active cells, numbered row by row, into tasks of the given granularity
+fn group_cells(width: usize, height: usize, granularity: Granularity) -> Vec<CellTask> {
+    let task = |cells: Vec<usize>| CellTask { cells, position: 0 };
+    match granularity {
+        Granularity::Cell => (0..width * height).map(|i| task(vec![i])).collect(),
+        Granularity::Row => (0..height).map(|row| task((row * width..(row + 1) * width).collect())).collect(),
+        Granularity::Block => {
+            let mut tasks = Vec::new();
+            for top in (0..height).step_by(BLOCK_SIZE) {
+                for left in (0..width).step_by(BLOCK_SIZE) {
+                    let cells = (top..(top + BLOCK_SIZE).min(height))
+                        .flat_map(|row| (left..(left + BLOCK_SIZE).min(width)).map(move |col| row * width + col))
+                        .collect();
+                    tasks.push(task(cells));
+                }
+            }
+            tasks
+        }
+    }
+}
+
+impl GridEngine for CellEngine {
+    /// Resumes one cell of the task the scheduler picks until every task is through its cells
+    fn step(&mut self, grid: &mut TGrid) {
+        debug_assert_eq!((grid.width(), grid.height()), (self.next_grid.width(), self.next_grid.height()));
+
+        for cell in &mut self.cells {
+            cell.restart();
+        }
+        for task in &mut self.tasks {
+            task.position = 0;
+        }
+
+        let infos = self.task_infos(grid);
+        let mut scheduler = self.schedule.policy.create_scheduler();
+        scheduler.start_generation(&infos);
+
+        let mut unfinished = vec![true; self.tasks.len()];
+        let mut remaining = self.tasks.len();
+        self.last_resumes = 0;
+        while remaining > 0 {
+            let t = scheduler.next_row(&unfinished);
+            let task = &mut self.tasks[t];
+            let cell = &mut self.cells[task.cells[task.position]];
+            self.last_resumes += 1;
+
+            if let Resume::Complete(alive) = cell.resume(grid, self.rule) {
+                self.next_grid[cell.row][cell.col] = alive;
+                task.position += 1;
+                if task.position == task.cells.len() {
+                    unfinished[t] = false;
+                    remaining -= 1;
+                }
+            }
+        }
+
+        std::mem::swap(grid, &mut self.next_grid);
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        *self = Self::new(width, height, self.rule, self.boundary, self.schedule);
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Rebuilds the coroutines with neighbour positions wrapped for `boundary`
+    fn set_boundary(&mut self, boundary: Boundary) {
+        *self = Self::new(self.next_grid.width(), self.next_grid.height(), self.rule, boundary, self.schedule);
+    }
+
+    fn last_slices(&self) -> Option<usize> {
+        Some(self.last_resumes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RULES;
+    use crate::scheduler::SCHEDULING_POLICIES;
+    use crate::test_support::assert_matches_closure_engine;
+    use crate::topology::BOUNDARIES;
+
+    #[test]
+    fn matches_the_closure_engine_for_every_schedule() {
+        // 19 x 10 leaves partial blocks along the right and bottom edges
+        for &granularity in GRANULARITIES {
+            for &policy in SCHEDULING_POLICIES {
+                let schedule = CellSchedule { granularity, policy };
+                let mut engine = CellEngine::new(19, 10, Rule::default(), Boundary::Torus, schedule);
+                assert_matches_closure_engine(&mut engine, 19, 10, Rule::default(), Boundary::Torus, 21, 5);
+                // Eight neighbours counted and one rule applied for every cell
+                assert_eq!(engine.last_slices(), Some(9 * 19 * 10));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_closure_engine_for_every_boundary_and_rule() {
+        for &boundary in BOUNDARIES {
+            for named in RULES {
+                let mut engine = CellEngine::new(16, 12, named.rule, boundary, CellSchedule::default());
+                assert_matches_closure_engine(&mut engine, 16, 12, named.rule, boundary, 22, 5);
+            }
+        }
+    }
+
+    #[test]
+    fn schedule_changes_keep_the_results() {
+        let mut engine = CellEngine::new(20, 20, Rule::default(), Boundary::DeadBorder, CellSchedule::default());
+        engine.set_schedule(CellSchedule { granularity: Granularity::Block, policy: SchedulingPolicy::Random });
+        assert_eq!(engine.task_count(), 9);
+        assert_matches_closure_engine(&mut engine, 20, 20, Rule::default(), Boundary::DeadBorder, 23, 5);
+
+        engine.set_schedule(CellSchedule { granularity: Granularity::Cell, policy: SchedulingPolicy::Activity });
+        assert_eq!(engine.task_count(), 400);
+        assert_matches_closure_engine(&mut engine, 20, 20, Rule::default(), Boundary::DeadBorder, 24, 5);
+    }
+
+    #[test]
+    fn cell_coroutine_yields_after_every_neighbour() {
+        let mut grid = TGrid::new(3, 3);
+        for (row, col) in [(1, 1), (1, 2), (2, 1)] {
+            grid[row][col] = true;
+        }
+        let mut cell = CellCoroutine::new(2, 2, Boundary::DeadBorder.neighbors(2, 2, 3, 3));
+        for _ in 0..8 {
+            assert!(matches!(cell.resume(&grid, Rule::default()), Resume::Yielded));
+        }
+        assert!(matches!(cell.resume(&grid, Rule::default()), Resume::Complete(true)));
+
+        cell.restart();
+        assert!(matches!(cell.resume(&grid, Rule::default()), Resume::Yielded));
+    }
+}
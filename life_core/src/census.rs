@@ -0,0 +1,387 @@
+// census.rs - Splits the live cells into separate objects and counts them by name, like a soup searcher's ash report
+// Objects start as 8-connected clusters, nearby clusters that change each other's evolution are merged into one
+// Each object is looked up in every phase and orientation of a table of common objects, the rest are named by what they do
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::classify::{self, Classification};
+use crate::rules::{Rule, CONWAY};
+use crate::sparse_engine::{Cell, SparseLife};
+use crate::universe::Universe;
+use crate::{CancelToken, GameOfLifeInterface};
+
+/// Generations the whole pattern and each object on its own are run for to see whether objects interact
+const SEPARATION_GENERATIONS: u64 = 8;
+
+/// Furthest a spark that dies out on its own can be from the object it is merged into, in rows or columns
+const SPARK_DISTANCE: i64 = 3;
+
+/// Generations an object missing from the table is run for to say what it does
+const UNNAMED_GENERATIONS: u64 = 256;
+
+/// A common object of Conway's Life, drawn as rows of `O` for live and `.` for dead cells in any one phase
+pub struct KnownObject {
+    pub name    : &'static str,
+    pub picture : &'static [&'static str],
+}
+
+/// The still lifes, oscillators and spaceships that make up most of the ash of random soups
+pub const KNOWN_OBJECTS: &[KnownObject] = &[
+    // Still lifes
+    KnownObject { name: "Block",                  picture: &["OO", "OO"] },
+    KnownObject { name: "Beehive",                picture: &[".OO.", "O..O", ".OO."] },
+    KnownObject { name: "Loaf",                   picture: &[".OO.", "O..O", ".O.O", "..O."] },
+    KnownObject { name: "Boat",                   picture: &["OO.", "O.O", ".O."] },
+    KnownObject { name: "Ship",                   picture: &["OO.", "O.O", ".OO"] },
+    KnownObject { name: "Tub",                    picture: &[".O.", "O.O", ".O."] },
+    KnownObject { name: "Pond",                   picture: &[".OO.", "O..O", "O..O", ".OO."] },
+    KnownObject { name: "Long boat",              picture: &["OO..", "O.O.", ".O.O", "..O."] },
+    KnownObject { name: "Long ship",              picture: &["OO..", "O.O.", ".O.O", "..OO"] },
+    KnownObject { name: "Barge",                  picture: &[".O..", "O.O.", ".O.O", "..O."] },
+    KnownObject { name: "Long barge",             picture: &[".O...", "O.O..", ".O.O.", "..O.O", "...O."] },
+    KnownObject { name: "Mango",                  picture: &[".OO..", "O..O.", ".O..O", "..OO."] },
+    KnownObject { name: "Snake",                  picture: &["OO.O", "O.OO"] },
+    KnownObject { name: "Aircraft carrier",       picture: &["OO..", "O..O", "..OO"] },
+    KnownObject { name: "Eater 1",                picture: &["OO..", "O.O.", "..O.", "..OO"] },
+    // Oscillators
+    KnownObject { name: "Blinker",                picture: &["OOO"] },
+    KnownObject { name: "Toad",                   picture: &[".OOO", "OOO."] },
+    KnownObject { name: "Beacon",                 picture: &["OO..", "OO..", "..OO", "..OO"] },
+    KnownObject { name: "Pulsar",                 picture: &[
+        "..OOO...OOO..",
+        ".............",
+        "O....O.O....O",
+        "O....O.O....O",
+        "O....O.O....O",
+        "..OOO...OOO..",
+        ".............",
+        "..OOO...OOO..",
+        "O....O.O....O",
+        "O....O.O....O",
+        "O....O.O....O",
+        ".............",
+        "..OOO...OOO..",
+    ] },
+    KnownObject { name: "Pentadecathlon",         picture: &["..O....O..", "OO.OOOO.OO", "..O....O.."] },
+    // Spaceships
+    KnownObject { name: "Glider",                 picture: &[".O.", "..O", "OOO"] },
+    KnownObject { name: "Lightweight spaceship",  picture: &[".O..O", "O....", "O...O", "OOOO."] },
+    KnownObject { name: "Middleweight spaceship", picture: &["...O..", ".O...O", "O.....", "O....O", "OOOOO."] },
+    KnownObject { name: "Heavyweight spaceship",  picture: &["...OO..", ".O....O", "O......", "O.....O", "OOOOOO."] },
+];
+
+impl KnownObject {
+    /// Live cells of the picture, its top-left corner at (0, 0)
+    pub fn cells(&self) -> Vec<Cell> {
+        self.picture.iter()
+            .enumerate()
+            .flat_map(|(row, line)| line.char_indices().filter(|&(_, c)| c == 'O').map(move |(col, _)| (row as i64, col as i64)))
+            .collect()
+    }
+}
+
+/// How many objects of one kind the census found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CensusEntry {
+    pub name  : String,
+    pub count : usize,
+}
+
+/// Every phase of every known object in all eight orientations, as normalised shapes
+fn known_shapes() -> &'static HashMap<Vec<Cell>, &'static str> {
+    static SHAPES: OnceLock<HashMap<Vec<Cell>, &'static str>> = OnceLock::new();
+    SHAPES.get_or_init(|| {
+        let mut shapes = HashMap::new();
+        for object in KNOWN_OBJECTS {
+            let mut universe = universe_of(&object.cells(), CONWAY);
+            let (_, first) = classify::normalise(universe.live_cells()).expect("known objects have live cells");
+            loop {
+                for orientation in 0..8 {
+                    let cells: Vec<Cell> = universe.live_cells().map(|&cell| orient(cell, orientation)).collect();
+                    let (_, shape) = classify::normalise(&cells).expect("known objects never die");
+                    shapes.insert(shape, object.name);
+                }
+                universe.update_generation();
+                if classify::normalise(universe.live_cells()).is_some_and(|(_, shape)| shape == first) { break; }
+            }
+        }
+        shapes
+    })
+}
+
+/// One of the eight rotations and reflections of a cell about the origin
+fn orient((row, col): Cell, orientation: u8) -> Cell {
+    let (row, col) = if orientation & 4 != 0 { (col, row) } else { (row, col) };
+    let row = if orientation & 2 != 0 { -row } else { row };
+    let col = if orientation & 1 != 0 { -col } else { col };
+    (row, col)
+}
+
+fn universe_of(cells: &[Cell], rule: Rule) -> SparseLife {
+    let mut universe = SparseLife::new(rule);
+    for &(row, col) in cells {
+        universe.set_cell(row, col, true);
+    }
+    universe
+}
+
+/// Live cells grouped into 8-connected clusters
+fn clusters(cells: impl IntoIterator<Item = Cell>) -> Vec<Vec<Cell>> {
+    let mut unvisited: HashSet<Cell> = cells.into_iter().collect();
+    let mut clusters = Vec::new();
+    while let Some(&seed) = unvisited.iter().next() {
+        unvisited.remove(&seed);
+        let mut cluster = vec![seed];
+        let mut next = 0;
+        while let Some(&(row, col)) = cluster.get(next) {
+            for neighbour in neighbourhood((row, col), 1) {
+                if unvisited.remove(&neighbour) {
+                    cluster.push(neighbour);
+                }
+            }
+            next += 1;
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// The cells up to `radius` rows and columns from a cell, itself included
+fn neighbourhood((row, col): Cell, radius: i64) -> impl Iterator<Item = Cell> {
+    (-radius..=radius).flat_map(move |dr| (-radius..=radius).map(move |dc| (row + dr, col + dc)))
+}
+
+/// Live cells of generations 0 to `SEPARATION_GENERATIONS`
+fn history(cells: &[Cell], rule: Rule) -> Vec<HashSet<Cell>> {
+    let mut universe = universe_of(cells, rule);
+    let mut history = vec![cells.iter().copied().collect()];
+    for _ in 0..SEPARATION_GENERATIONS {
+        universe.update_generation();
+        history.push(universe.live_cells().copied().collect());
+    }
+    history
+}
+
+/// Objects that have to be merged, found at the first generation where running them apart disagrees with the whole
+/// Up to then the whole is the union of the parts, so a cell that differs has neighbours from every object it came from
+fn first_interaction(whole: &[HashSet<Cell>], parts: &[Vec<HashSet<Cell>>]) -> Vec<(usize, usize)> {
+    let owners = |generation: usize| {
+        let mut owners: HashMap<Cell, usize> = HashMap::new();
+        let mut overlaps = Vec::new();
+        for (index, part) in parts.iter().enumerate() {
+            for &cell in &part[generation] {
+                if let Some(other) = owners.insert(cell, index) {
+                    overlaps.push((other, index));
+                }
+            }
+        }
+        (owners, overlaps)
+    };
+
+    for (generation, cells) in whole.iter().enumerate().skip(1) {
+        let (current, mut merges) = owners(generation);
+        let differing: Vec<Cell> = cells.iter().filter(|cell| !current.contains_key(cell))
+            .chain(current.keys().filter(|cell| !cells.contains(cell)))
+            .copied()
+            .collect();
+        if differing.is_empty() && merges.is_empty() { continue; }
+
+        let (previous, _) = owners(generation - 1);
+        for cell in differing {
+            let mut sources = neighbourhood(cell, 1).filter_map(|neighbour| previous.get(&neighbour).copied());
+            if let Some(first) = sources.next() {
+                merges.extend(sources.map(|other| (first, other)).filter(|&(a, b)| a != b));
+            }
+        }
+        return merges;
+    }
+    Vec::new()
+}
+
+/// A spark that dies out on its own joins an object close enough to have thrown it off
+fn spark(objects: &[Vec<Cell>], parts: &[Vec<HashSet<Cell>>]) -> Option<(usize, usize)> {
+    let owners: HashMap<Cell, usize> = objects.iter()
+        .enumerate()
+        .flat_map(|(index, object)| object.iter().map(move |&cell| (cell, index)))
+        .collect();
+    parts.iter()
+        .enumerate()
+        .filter(|(_, part)| part.last().is_some_and(|cells| cells.is_empty()))
+        .find_map(|(index, _)| {
+            objects[index].iter()
+                .flat_map(|&cell| neighbourhood(cell, SPARK_DISTANCE))
+                .find_map(|cell| owners.get(&cell).copied().filter(|&other| other != index))
+                .map(|other| (index, other))
+        })
+}
+
+/// Splits live cells into objects under `rule`, starting from 8-connected clusters
+/// The whole pattern is run for a few generations and compared with each object run on its own,
+/// objects are merged where the two disagree until every object evolves as if it were alone
+/// Sparks that die out on their own, like those thrown off by oscillators, are then merged into a neighbour
+/// Objects that sit side by side without affecting each other stay apart, as in a pseudo still life
+pub fn separate(cells: impl IntoIterator<Item = Cell>, rule: Rule) -> Vec<Vec<Cell>> {
+    separate_cancellable(cells, rule, &CancelToken::default()).expect("separation cancelled without a cancel token")
+}
+
+/// Like `separate`, but gives up once `cancel` is set and returns `None`
+/// It is checked before each object is run on its own, a large soup can take many rounds of merging
+pub fn separate_cancellable(cells: impl IntoIterator<Item = Cell>, rule: Rule, cancel: &CancelToken) -> Option<Vec<Vec<Cell>>> {
+    let mut objects = clusters(cells);
+    let whole = history(&objects.concat(), rule);
+
+    loop {
+        let mut parts: Vec<Vec<HashSet<Cell>>> = Vec::with_capacity(objects.len());
+        for object in &objects {
+            if cancel.is_cancelled() { return None; }
+            parts.push(history(object, rule));
+        }
+        let mut merges = first_interaction(&whole, &parts);
+        if merges.is_empty() {
+            merges.extend(spark(&objects, &parts));
+        }
+        if merges.is_empty() { return Some(objects); }
+
+        // Each object joins the lowest-numbered one it is merged with, following chains of merges
+        let mut joined: Vec<usize> = (0..objects.len()).collect();
+        let root = |joined: &Vec<usize>, mut index: usize| {
+            while joined[index] != index { index = joined[index]; }
+            index
+        };
+        for (a, b) in merges {
+            let (a, b) = (root(&joined, a), root(&joined, b));
+            joined[a.max(b)] = a.min(b);
+        }
+
+        let mut merged: HashMap<usize, Vec<Cell>> = HashMap::new();
+        for (index, object) in objects.into_iter().enumerate() {
+            merged.entry(root(&joined, index)).or_default().extend(object);
+        }
+        objects = merged.into_values().collect();
+    }
+}
+
+/// The name of a known object, otherwise what it does and its size
+/// Objects are only named under Conway's rule, the table means nothing under others
+pub fn identify(object: &[Cell], rule: Rule) -> String {
+    if rule == CONWAY
+        && let Some((_, shape)) = classify::normalise(object)
+        && let Some(name) = known_shapes().get(&shape) {
+        return name.to_string();
+    }
+
+    let analysis = classify::classify(object.iter().copied(), rule, UNNAMED_GENERATIONS);
+    match analysis.classification {
+        _ if analysis.transient > 0 => "Unsettled".to_string(),
+        Classification::StillLife => format!("Still life, {} cells", object.len()),
+        Classification::Oscillator { period, .. } => format!("Period-{} oscillator, {} cells", period, object.len()),
+        Classification::Spaceship { period, displacement } => format!("{} spaceship, {} cells", classify::speed(period, displacement), object.len()),
+        Classification::Empty | Classification::Unknown => "Unsettled".to_string(),
+    }
+}
+
+/// Counts the objects among `cells` by name, most common first
+pub fn census(cells: impl IntoIterator<Item = Cell>, rule: Rule) -> Vec<CensusEntry> {
+    census_cancellable(cells, rule, &CancelToken::default()).expect("census cancelled without a cancel token")
+}
+
+/// Like `census`, but gives up once `cancel` is set and returns `None`, while separating the objects or between naming them
+pub fn census_cancellable(cells: impl IntoIterator<Item = Cell>, rule: Rule, cancel: &CancelToken) -> Option<Vec<CensusEntry>> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for object in separate_cancellable(cells, rule, cancel)? {
+        if cancel.is_cancelled() { return None; }
+        *counts.entry(identify(&object, rule)).or_insert(0) += 1;
+    }
+
+    let mut entries: Vec<CensusEntry> = counts.into_iter().map(|(name, count)| CensusEntry { name, count }).collect();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::grid::TGrid;
+    use crate::patterns;
+
+    /// `cells` moved down and right by `(rows, cols)`
+    fn at(cells: &[Cell], (rows, cols): Cell) -> Vec<Cell> {
+        cells.iter().map(|&(row, col)| (row + rows, col + cols)).collect()
+    }
+
+    const BLOCK: &[Cell] = &[(0, 0), (0, 1), (1, 0), (1, 1)];
+    const BLINKER: &[Cell] = &[(0, 0), (0, 1), (0, 2)];
+    const GLIDER: &[Cell] = &[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+    fn entry(name: &str, count: usize) -> CensusEntry {
+        CensusEntry { name: name.to_string(), count }
+    }
+
+    #[test]
+    fn separate_objects_are_named_and_counted() {
+        let cells = [at(BLOCK, (0, 0)), at(BLINKER, (0, 20)), at(GLIDER, (20, 0))].concat();
+        assert_eq!(census(cells, CONWAY), vec![entry("Blinker", 1), entry("Block", 1), entry("Glider", 1)]);
+    }
+
+    #[test]
+    fn entries_come_most_common_first() {
+        let cells = [at(BLOCK, (0, 0)), at(BLOCK, (0, 10)), at(BLOCK, (10, 0)), at(BLINKER, (20, 20))].concat();
+        assert_eq!(census(cells, CONWAY), vec![entry("Block", 3), entry("Blinker", 1)]);
+    }
+
+    #[test]
+    fn objects_are_named_in_any_phase_and_orientation() {
+        // The glider reflected and a generation on, the blinker standing upright
+        let glider = [(0, 0), (0, 2), (1, 1), (1, 2), (2, 1)];
+        let blinker = [(0, 0), (1, 0), (2, 0)];
+        assert_eq!(identify(&glider, CONWAY), "Glider");
+        assert_eq!(identify(&blinker, CONWAY), "Blinker");
+    }
+
+    #[test]
+    fn clusters_that_interact_are_one_object() {
+        // Two blocks a cell apart are two clusters, but each stays a block beside the other
+        let apart = [at(BLOCK, (0, 0)), at(BLOCK, (0, 3))].concat();
+        assert_eq!(separate(apart, CONWAY).len(), 2);
+
+        // A blinker next to a block changes it, so the two are counted as one object
+        let touching = [at(BLOCK, (0, 0)), at(BLINKER, (3, 0))].concat();
+        assert_eq!(separate(touching, CONWAY).len(), 1);
+    }
+
+    #[test]
+    fn objects_are_only_named_under_conway() {
+        let highlife = Rule::new(&[3, 6], &[2, 3]);
+        assert_eq!(census(BLOCK.iter().copied(), highlife), vec![entry("Still life, 4 cells", 1)]);
+    }
+
+    #[test]
+    fn cancelled_census_gives_up() {
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert_eq!(census_cancellable(BLOCK.iter().copied(), CONWAY, &cancel), None);
+    }
+
+    #[test]
+    fn cancelling_a_large_census_stops_it_separating() {
+        let mut grid = TGrid::new(200, 200);
+        patterns::apply_random_pattern(&mut grid, 5);
+        let cells = classify::grid_cells(&grid);
+
+        let cancel = CancelToken::default();
+        let started = Instant::now();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(20));
+                cancel.cancel();
+            });
+            assert_eq!(census_cancellable(cells, CONWAY, &cancel), None);
+        });
+        // Separating this soup to the end takes about a minute in a debug build
+        assert!(started.elapsed() < Duration::from_secs(10), "took {:?} to give up", started.elapsed());
+    }
+}
@@ -0,0 +1,285 @@
+// classify.rs - Names what a pattern settles into: still life, oscillator, spaceship or nothing recognisable
+// The cells are run forward on an unbounded sparse universe, so the grid's edges neither clip nor wrap them
+// Generations are compared as shapes with their bounding box moved to the origin, which lets a spaceship match itself
+// A bounded grid's wrapping or dead edges are ignored, the answer is what the cells would do on an infinite plane
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::grid::TGrid;
+use crate::rules::Rule;
+use crate::sparse_engine::{Cell, SparseLife};
+use crate::universe::Universe;
+use crate::{CancelToken, GameOfLifeInterface};
+
+/// Generations the apps run a pattern for before calling it unknown
+pub const MAX_GENERATIONS: u64 = 1000;
+
+/// Most live cells the apps classify or take a census of, running more forward would stall the simulation
+pub const MAX_POPULATION: u64 = 5_000;
+
+/// What a pattern does once it repeats
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Classification {
+    Empty,                                  // No live cells left
+    StillLife,
+    Oscillator {
+        period : u64,
+        rotor  : Vec<Cell>,                 // Cells that change during a period
+        stator : Vec<Cell>,                 // Cells live in every phase
+    },
+    Spaceship {
+        period       : u64,
+        displacement : (i64, i64),          // Rows and columns travelled each period
+    },
+    Unknown,                                // Nothing repeated within the generations run
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Empty => write!(f, "Empty"),
+            Classification::StillLife => write!(f, "Still life"),
+            Classification::Oscillator { period, rotor, stator } => {
+                write!(f, "Period-{} oscillator, {} rotor and {} stator cells", period, rotor.len(), stator.len())
+            }
+            Classification::Spaceship { period, displacement } => {
+                write!(f, "Period-{} spaceship, {}", period, speed(*period, *displacement))
+            }
+            Classification::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// A classification and how long the pattern took to get there
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    pub classification : Classification,
+    pub transient      : u64,     // Generations before the first one that repeats, 0 if the pattern given already does
+    pub generations    : u64,     // Generations run to decide
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.classification, self.transient) {
+            (Classification::Unknown, _) => write!(f, "Unknown, no repeat within {} generations", self.generations),
+            (Classification::Empty, 0) => write!(f, "Empty"),
+            (Classification::Empty, transient) => write!(f, "Dies out after {}", generations(transient)),
+            (classification, 0) => write!(f, "{}", classification),
+            (classification, transient) => write!(f, "{} after {}", classification, generations(transient)),
+        }
+    }
+}
+
+fn generations(count: u64) -> String {
+    if count == 1 { "1 generation".to_string() } else { format!("{} generations", count) }
+}
+
+/// Speed in the usual notation, c/4 diagonal for the glider, c/2 orthogonal for the lightweight spaceship
+/// Ships that move off both axes and diagonals are written with their displacement, (2,1)c/6 for a knightship
+pub fn speed(period: u64, displacement: (i64, i64)) -> String {
+    let (rows, cols) = (displacement.0.unsigned_abs(), displacement.1.unsigned_abs());
+    let (major, minor) = (rows.max(cols), rows.min(cols));
+    if minor != 0 && minor != major {
+        return format!("({},{})c/{} oblique", major, minor, period);
+    }
+
+    let divisor = gcd(major, period);
+    let direction = if minor == 0 { "orthogonal" } else { "diagonal" };
+    match (major / divisor, period / divisor) {
+        (1, period) => format!("c/{} {}", period, direction),
+        (distance, period) => format!("{}c/{} {}", distance, period, direction),
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Live cells moved so their bounding box starts at (0, 0) and sorted, along with the (row, col) they were moved from
+pub fn normalise<'a>(cells: impl IntoIterator<Item = &'a Cell>) -> Option<(Cell, Vec<Cell>)> {
+    let mut shape: Vec<Cell> = cells.into_iter().copied().collect();
+    let top = shape.iter().map(|&(row, _)| row).min()?;
+    let left = shape.iter().map(|&(_, col)| col).min()?;
+    for (row, col) in &mut shape {
+        *row -= top;
+        *col -= left;
+    }
+    shape.sort_unstable();
+    Some(((top, left), shape))
+}
+
+/// Live cells of the active area at their grid (row, col)
+pub fn grid_cells(grid: &TGrid) -> Vec<Cell> {
+    grid.rows()
+        .flat_map(|row| grid.cols().map(move |col| (row, col)))
+        .filter(|&(row, col)| grid[row][col])
+        .map(|(row, col)| (row as i64, col as i64))
+        .collect()
+}
+
+fn hash_shape(shape: &[Cell]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    shape.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// `cells` as a sparse universe `generations` steps later
+fn run(cells: &[Cell], rule: Rule, generations: u64) -> SparseLife {
+    let mut universe = SparseLife::new(rule);
+    for &(row, col) in cells {
+        universe.set_cell(row, col, true);
+    }
+    for _ in 0..generations {
+        universe.update_generation();
+    }
+    universe
+}
+
+/// Runs `cells` forward under `rule` until a generation repeats an earlier one, possibly moved, for at most `max_generations`
+/// Only shape hashes are remembered, a matching hash is confirmed by running the start again to the earlier generation
+/// The rotor and stator come back in the coordinates the cells were given in, as they are once the pattern repeats
+pub fn classify(cells: impl IntoIterator<Item = Cell>, rule: Rule, max_generations: u64) -> Analysis {
+    classify_cancellable(cells, rule, max_generations, &CancelToken::default())
+        .expect("classification cancelled without a cancel token")
+}
+
+/// Like `classify`, but gives up between generations once `cancel` is set and returns `None`
+pub fn classify_cancellable(cells: impl IntoIterator<Item = Cell>, rule: Rule, max_generations: u64, cancel: &CancelToken) -> Option<Analysis> {
+    let start: Vec<Cell> = cells.into_iter().collect();
+    let mut universe = run(&start, rule, 0);
+    let mut seen: HashMap<u64, Vec<(u64, Cell)>> = HashMap::new();   // Shape hash to each generation and origin with it
+
+    for generation in 0..=max_generations {
+        if cancel.is_cancelled() { return None; }
+
+        let Some((origin, shape)) = normalise(universe.live_cells()) else {
+            return Some(Analysis { classification: Classification::Empty, transient: generation, generations: generation });
+        };
+
+        let hash = hash_shape(&shape);
+        let repeat = seen.get(&hash).into_iter().flatten().find(|&&(earlier, _)| {
+            let earlier_universe = run(&start, rule, earlier);
+            normalise(earlier_universe.live_cells()).is_some_and(|(_, earlier_shape)| earlier_shape == shape)
+        });
+
+        if let Some(&(earlier, earlier_origin)) = repeat {
+            let period = generation - earlier;
+            let displacement = (origin.0 - earlier_origin.0, origin.1 - earlier_origin.1);
+            let classification = match (period, displacement) {
+                (1, (0, 0)) => Classification::StillLife,
+                (_, (0, 0)) => oscillator(universe, period),
+                _ => Classification::Spaceship { period, displacement },
+            };
+            return Some(Analysis { classification, transient: earlier, generations: generation });
+        }
+
+        seen.entry(hash).or_default().push((generation, origin));
+        if generation < max_generations {
+            universe.update_generation();
+        }
+    }
+
+    Some(Analysis { classification: Classification::Unknown, transient: 0, generations: max_generations })
+}
+
+/// Splits the cells of one full period into those live in every phase and those that change
+fn oscillator(mut universe: SparseLife, period: u64) -> Classification {
+    let mut live_phases: HashMap<Cell, u64> = HashMap::new();
+    for _ in 0..period {
+        for &cell in universe.live_cells() {
+            *live_phases.entry(cell).or_insert(0) += 1;
+        }
+        universe.update_generation();
+    }
+
+    let (mut rotor, mut stator) = (Vec::new(), Vec::new());
+    for (cell, phases) in live_phases {
+        if phases == period { stator.push(cell) } else { rotor.push(cell) }
+    }
+    rotor.sort_unstable();
+    stator.sort_unstable();
+    Classification::Oscillator { period, rotor, stator }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_cells(cells: &[Cell]) -> Analysis {
+        classify(cells.iter().copied(), Rule::default(), MAX_GENERATIONS)
+    }
+
+    #[test]
+    fn block_is_a_still_life() {
+        let analysis = classify_cells(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(analysis.classification, Classification::StillLife);
+        assert_eq!(analysis.transient, 0);
+    }
+
+    #[test]
+    fn blinker_is_a_period_2_oscillator_with_its_centre_as_stator() {
+        let analysis = classify_cells(&[(5, 4), (5, 5), (5, 6)]);
+        let Classification::Oscillator { period, rotor, stator } = analysis.classification else {
+            panic!("expected an oscillator, got {}", analysis);
+        };
+        assert_eq!(period, 2);
+        assert_eq!(stator, vec![(5, 5)]);
+        assert_eq!(rotor, vec![(4, 5), (5, 4), (5, 6), (6, 5)]);
+        assert_eq!(analysis.transient, 0);
+    }
+
+    #[test]
+    fn glider_is_a_diagonal_spaceship() {
+        let analysis = classify_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(analysis.classification, Classification::Spaceship { period: 4, displacement: (1, 1) });
+        assert_eq!(analysis.classification.to_string(), "Period-4 spaceship, c/4 diagonal");
+    }
+
+    #[test]
+    fn lightweight_spaceship_is_an_orthogonal_spaceship() {
+        let analysis = classify_cells(&[(0, 1), (0, 4), (1, 0), (2, 0), (2, 4), (3, 0), (3, 1), (3, 2), (3, 3)]);
+        assert_eq!(analysis.classification, Classification::Spaceship { period: 4, displacement: (0, -2) });
+    }
+
+    #[test]
+    fn transient_counts_the_generations_before_the_repeat() {
+        // Three cells of a block fill in the fourth after one generation
+        let analysis = classify_cells(&[(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(analysis.classification, Classification::StillLife);
+        assert_eq!(analysis.transient, 1);
+        assert_eq!(analysis.to_string(), "Still life after 1 generation");
+
+        let analysis = classify_cells(&[(0, 0)]);
+        assert_eq!(analysis.classification, Classification::Empty);
+        assert_eq!(analysis.to_string(), "Dies out after 1 generation");
+    }
+
+    #[test]
+    fn nothing_repeating_within_the_limit_is_unknown() {
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        let analysis = classify(glider, Rule::default(), 3);
+        assert_eq!(analysis.classification, Classification::Unknown);
+        assert_eq!(analysis.generations, 3);
+    }
+
+    #[test]
+    fn cancelled_classification_gives_up() {
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        assert_eq!(classify_cancellable([(0, 0), (0, 1), (1, 0), (1, 1)], Rule::default(), MAX_GENERATIONS, &cancel), None);
+    }
+
+    #[test]
+    fn speed_strings() {
+        assert_eq!(speed(4, (1, 1)), "c/4 diagonal");
+        assert_eq!(speed(4, (-1, 1)), "c/4 diagonal");
+        assert_eq!(speed(4, (0, -2)), "c/2 orthogonal");
+        assert_eq!(speed(3, (0, 1)), "c/3 orthogonal");
+        assert_eq!(speed(5, (2, 0)), "2c/5 orthogonal");
+        assert_eq!(speed(6, (2, 1)), "(2,1)c/6 oblique");
+        assert_eq!(speed(6, (-1, 2)), "(2,1)c/6 oblique");
+    }
+}
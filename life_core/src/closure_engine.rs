@@ -0,0 +1,147 @@
+// closure_engine.rs - Function factory engine with one closure per cell
+// Each cell function has baked-in coordinates and neighbor positions
+
+use crate::grid::TGrid;
+use crate::rules::Rule;
+use crate::topology::Boundary;
+use crate::GridEngine;
+
+pub type CellFn = dyn Fn(&TGrid, &mut TGrid) + Send + Sync;
+pub type SmartCellFn = Box<CellFn>;
+
+/// Function factory that creates a specialized function for the cell at (row, col)
+/// The rule and the neighbor positions, already wrapped for the boundary, are baked in alongside the coordinates
+pub fn create_cell_fn(row: usize, col: usize, rule: Rule, neighbors: [(usize, usize); 8]) -> impl Fn(&TGrid, &mut TGrid) + Send + Sync {
+    move |current_grid: &TGrid, next_grid: &mut TGrid| {
+        // Count live neighbors using baked-in coordinates
+        let mut count = 0;
+
+        for &(nr, nc) in &neighbors {  if current_grid[nr][nc] {count+=1;} }
+
+        // Apply the rule and write directly to next_grid
+        next_grid[row][col] = rule.next_state(current_grid[row][col], count);
+    }
+}
+
+/// Holds one cell function per active cell and the grid they write into
+pub struct ClosureEngine {
+    cell_functions : Vec<SmartCellFn>,
+    next_grid      : TGrid,
+    rule           : Rule,
+    boundary       : Boundary,
+}
+
+impl ClosureEngine {
+    pub fn new(width: usize, height: usize, rule: Rule, boundary: Boundary) -> Self {
+        let next_grid = TGrid::new(width, height);
+
+        // Create a single vector of width * height lambda functions
+        let mut cell_functions = Vec::with_capacity(next_grid.active_cells());
+
+        // Initialize functions with coordinates covering the active area
+        for grid_row in next_grid.rows() {
+            for grid_col in next_grid.cols() {
+                let neighbors = boundary.neighbors(grid_row, grid_col, width, height);
+                cell_functions.push(Box::new(create_cell_fn(grid_row, grid_col, rule, neighbors)) as SmartCellFn);
+            }
+        }
+
+        Self {
+            cell_functions,
+            next_grid,
+            rule,
+            boundary,
+        }
+    }
+}
+
+impl GridEngine for ClosureEngine {
+    /// Rebuilds the cell functions for a new active area
+    fn resize(&mut self, width: usize, height: usize) {
+        *self = Self::new(width, height, self.rule, self.boundary);
+    }
+
+    /// Rebuilds the cell functions with a different rule baked in
+    fn set_rule(&mut self, rule: Rule) {
+        *self = Self::new(self.next_grid.width(), self.next_grid.height(), rule, self.boundary);
+    }
+
+    /// Rebuilds the cell functions with neighbor positions wrapped for `boundary`
+    fn set_boundary(&mut self, boundary: Boundary) {
+        *self = Self::new(self.next_grid.width(), self.next_grid.height(), self.rule, boundary);
+    }
+
+    /// Runs every cell function against `grid` and replaces it with the next generation
+    fn step(&mut self, grid: &mut TGrid) {
+        debug_assert_eq!((grid.width(), grid.height()), (self.next_grid.width(), self.next_grid.height()));
+
+        // Clear the next grid first
+        self.next_grid.clear();
+
+        // Execute all cell functions, each writes directly to next_grid
+        self.cell_functions.iter().for_each(|cell_func| {
+            cell_func(grid, &mut self.next_grid);
+        });
+
+        // Assert that border cells remain dead (error checking) - debug only
+        debug_assert!(self.next_grid.check_border_cells_dead());
+
+        // Swap next generation into place, the old grid becomes the scratch buffer
+        std::mem::swap(grid, &mut self.next_grid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::grid_with;
+
+    #[test]
+    fn blinker_alternates_between_row_and_column() {
+        let horizontal = grid_with(5, 5, &[(3, 2), (3, 3), (3, 4)]);
+        let vertical = grid_with(5, 5, &[(2, 3), (3, 3), (4, 3)]);
+        let mut engine = ClosureEngine::new(5, 5, Rule::default(), Boundary::DeadBorder);
+
+        let mut grid = horizontal.clone();
+        engine.step(&mut grid);
+        assert_eq!(grid, vertical);
+        engine.step(&mut grid);
+        assert_eq!(grid, horizontal);
+    }
+
+    #[test]
+    fn glider_moves_one_cell_diagonally_every_four_generations() {
+        let glider = [(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)];
+        let mut grid = grid_with(10, 10, &glider);
+        let mut engine = ClosureEngine::new(10, 10, Rule::default(), Boundary::DeadBorder);
+
+        for _ in 0..4 {
+            engine.step(&mut grid);
+        }
+        assert_eq!(grid, grid_with(10, 10, &glider.map(|(row, col)| (row + 1, col + 1))));
+    }
+
+    #[test]
+    fn glider_wraps_round_a_torus() {
+        let glider = [(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)];
+        let start = grid_with(6, 6, &glider);
+        let mut engine = ClosureEngine::new(6, 6, Rule::default(), Boundary::Torus);
+
+        // Six moves of one cell bring it back to where it started
+        let mut grid = start.clone();
+        for _ in 0..24 {
+            engine.step(&mut grid);
+        }
+        assert_eq!(grid, start);
+    }
+
+    #[test]
+    fn glider_dies_against_a_dead_border() {
+        let mut grid = grid_with(6, 6, &[(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)]);
+        let mut engine = ClosureEngine::new(6, 6, Rule::default(), Boundary::DeadBorder);
+        for _ in 0..40 {
+            engine.step(&mut grid);
+        }
+        assert_eq!(grid.count_live_cells(), 4, "expected the glider to end as a block in the corner");
+    }
+}
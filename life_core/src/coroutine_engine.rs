@@ -0,0 +1,628 @@
+// coroutine_engine.rs - Time-sliced row coroutines
+// Each active row is computed by a closure-based coroutine that stops when its time budget runs out
+// Every slice is recorded, so the last generation can be inspected row by row
+// Optionally the next generation is shared as it is computed, so it can be drawn before it is finished
+// A generation can be cancelled between slices, which recreates the coroutines so no progress carries over
+// Processors can share one scheduler, which interleaves the rows of all of them on one runtime
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{oneshot, Notify};
+
+use crate::cancel::CancelToken;
+use crate::grid::{TGrid, TRow, GRID_START};
+use crate::rules::Rule;
+use crate::scheduler::{RowInfo, Scheduler, SchedulingPolicy};
+use crate::topology::Boundary;
+
+pub type RowFuture = Pin<Box<dyn Future<Output = SliceOutcome> + Send>>;
+/// Called with the grid, the slice budget and whether an unfinished slice should hand back the row so far
+pub type RowCoroutine = Box<dyn FnMut(Arc<TGrid>, Duration, bool) -> RowFuture + Send>;
+
+/// Generation deadline until `set_deadline` is called, the apps' default update interval
+const DEFAULT_DEADLINE: Duration = Duration::from_millis(200);
+
+/// Slices kept for the timeline per band, later ones are only counted
+const MAX_RECORDED_SLICES: usize = 100_000;
+
+/// What one time slice of a row coroutine got done
+pub struct SliceOutcome {
+    pub is_complete : bool,
+    pub result      : Option<TRow>,  // The row, once complete or when asked for the unfinished columns too
+    pub start_col   : usize,         // Column the slice resumed at
+    pub end_col     : usize,         // First column left for the next slice
+}
+
+/// One slice on the timeline, times are from the start of the generation
+#[derive(Clone, Copy, Debug)]
+pub struct SliceRecord {
+    pub row       : usize,
+    pub band      : usize,      // Worker that ran it, 0 on a single thread
+    pub start     : Duration,
+    pub duration  : Duration,
+    pub start_col : usize,
+    pub end_col   : usize,
+    pub completed : bool,       // False when the budget expired mid-row
+}
+
+/// Totals for one row over a generation
+#[derive(Clone, Copy, Debug)]
+pub struct RowStats {
+    pub row         : usize,
+    pub slices      : usize,
+    pub expired     : usize,       // Slices that ran out of budget before the end of the row
+    pub busy        : Duration,    // Time spent inside the row's slices
+    pub finished_at : Duration,    // When the completing slice ended, from the start of the generation
+}
+
+/// Everything recorded while computing the last generation
+#[derive(Clone, Debug, Default)]
+pub struct GenerationStats {
+    pub wall_time      : Duration,
+    pub rows           : Vec<RowStats>,       // In row order
+    pub slices         : Vec<SliceRecord>,    // In the order they started
+    pub dropped_slices : usize,               // Slices past MAX_RECORDED_SLICES per band, counted in `rows` only
+    pub cancelled      : bool,                // Abandoned part way, the rows that finished are still counted
+}
+
+/// The next generation as far as it has been computed, shared with whoever draws it
+#[derive(Default)]
+pub struct PartialGeneration {
+    state: Mutex<PartialState>,
+}
+
+/// Progress at one moment, see `PartialGeneration::snapshot`
+#[derive(Clone, Default)]
+pub struct PartialState {
+    pub in_progress : bool,
+    pub next_grid   : TGrid,        // Cells computed so far, the rest are dead
+    pub cursors     : Vec<usize>,   // Per grid row, the column the last slice stopped at, past the width once the row is done
+}
+
+impl PartialState {
+    pub fn row_finished(&self, row: usize) -> bool {
+        self.cursors[row] > self.next_grid.width()
+    }
+}
+
+impl PartialGeneration {
+    pub fn snapshot(&self) -> PartialState {
+        self.state.lock().unwrap().clone()
+    }
+
+    fn begin(&self, width: usize, height: usize) {
+        *self.state.lock().unwrap() = PartialState {
+            in_progress : true,
+            next_grid   : TGrid::new(width, height),
+            cursors     : vec![GRID_START; height + 2],
+        };
+    }
+
+    /// Copies the columns a slice computed, unfinished rows included, the slice must have handed back its row
+    fn record(&self, row: usize, outcome: &SliceOutcome) {
+        let result = outcome.result.as_ref().expect("slices share their row while progress is recorded");
+        let mut state = self.state.lock().unwrap();
+        state.next_grid[row][outcome.start_col..outcome.end_col].copy_from_slice(&result[outcome.start_col..outcome.end_col]);
+        state.cursors[row] = outcome.end_col;
+    }
+
+    fn end(&self) {
+        self.state.lock().unwrap().in_progress = false;
+    }
+}
+
+/// Where a driver reports its slices
+#[derive(Clone)]
+struct SliceLog {
+    band     : usize,                            // Worker that runs the slices, 0 on a single thread
+    started  : Instant,                          // Start of the generation
+    progress : Option<Arc<PartialGeneration>>,   // Set when progressive rendering is on
+}
+
+impl GenerationStats {
+    pub fn total_slices(&self) -> usize {
+        self.rows.iter().map(|row| row.slices).sum()
+    }
+
+    pub fn total_expired(&self) -> usize {
+        self.rows.iter().map(|row| row.expired).sum()
+    }
+}
+
+/// Where a row coroutine has got to, kept between slices
+/// Shared with the future of each slice, which writes its progress back before it yields
+pub struct RowState {
+    pub current_col : usize,    // Next column to compute, back at GRID_START once the row is complete
+    pub result      : TRow,     // Columns before `current_col` are this generation's
+}
+
+impl RowState {
+    pub fn new(width: usize) -> Self {
+        Self { current_col: GRID_START, result: vec![false; width + 2] }
+    }
+}
+
+/// Factory function that creates time-sliced row coroutine closures
+/// `width` is the active width, the row result includes both border cells
+/// Each slice resumes at the column the last one stopped at and computes at least one cell, however small the budget
+/// The row is only copied out when it is complete, or on every slice when `share_partial` asks for it
+pub fn create_time_sliced_row_coroutine(row_index: usize, width: usize, rule: Rule, boundary: Boundary) -> impl FnMut(Arc<TGrid>, Duration, bool) -> RowFuture + Send {
+    let row_end = width + 1;
+    let state = Arc::new(Mutex::new(RowState::new(width)));
+
+    move |current_grid: Arc<TGrid>, time_budget: Duration, share_partial: bool| {
+        let state = state.clone();
+
+        Box::pin(async move {
+            // The lock is only held while computing, never across the yield
+            let outcome = {
+                let mut state = state.lock().unwrap();
+                let RowState { current_col, result } = &mut *state;
+                let start = Instant::now();
+                let start_col = *current_col;
+
+                while *current_col < row_end {
+                    // Check if time budget is exhausted, a slice always computes at least one cell
+                    if *current_col > start_col && start.elapsed() >= time_budget {
+                        break;  // Time's up, exit and yield control
+                    }
+
+                    let col = *current_col;
+                    let mut count = 0;
+
+                    // Neighbor positions for this specific row, wrapped for the boundary
+                    let neighbors = boundary.neighbors(row_index, col, width, current_grid.height());
+
+                    for &(nr, nc) in &neighbors {
+                        if current_grid[nr][nc] { count += 1; }
+                    }
+
+                    result[col] = rule.next_state(current_grid[row_index][col], count);
+                    *current_col += 1;
+                }
+
+                let end_col = *current_col;
+                let is_complete = end_col >= row_end;
+                if is_complete {
+                    *current_col = GRID_START;  // Ready for the next generation
+                }
+                let result = (is_complete || share_partial).then(|| result.clone());
+                SliceOutcome { is_complete, result, start_col, end_col }
+            };
+
+            // Check if row is complete, an unfinished row suspends so the executor can run other tasks
+            if !outcome.is_complete {
+                tokio::task::yield_now().await;
+            }
+            outcome
+        })
+    }
+}
+
+/// One band of row coroutines on its way through a generation, given one time slice at a time
+/// It owns the coroutines while it runs, they go back to the processor with its results
+struct BandRun {
+    row_coroutines : Vec<RowCoroutine>,
+    rows           : Vec<RowInfo>,
+    scheduler      : Box<dyn Scheduler>,   // Picks the band's next row by the processor's policy
+    current_grid   : Arc<TGrid>,
+    time_budget    : Duration,
+    log            : SliceLog,
+    cancel         : CancelToken,
+    unfinished     : Vec<bool>,            // Rows still waiting for a complete slice
+    results        : Vec<TRow>,            // Store completed row results
+    remaining      : usize,
+    stats          : GenerationStats,
+}
+
+/// A band handed back: its coroutines, its rows unless it was cancelled, and its slices
+type FinishedBand = (Vec<RowCoroutine>, Option<Vec<TRow>>, GenerationStats);
+
+impl BandRun {
+    fn new(row_coroutines: Vec<RowCoroutine>, rows: Vec<RowInfo>, mut scheduler: Box<dyn Scheduler>, current_grid: Arc<TGrid>, time_budget: Duration, log: SliceLog, cancel: CancelToken) -> Self {
+        scheduler.start_generation(&rows);
+        let stats = GenerationStats {
+            rows: rows.iter()
+                .map(|info| RowStats { row: info.row, slices: 0, expired: 0, busy: Duration::ZERO, finished_at: Duration::ZERO })
+                .collect(),
+            ..GenerationStats::default()
+        };
+        Self {
+            unfinished: vec![true; row_coroutines.len()],
+            results: vec![TRow::new(); row_coroutines.len()],
+            remaining: row_coroutines.len(),
+            row_coroutines,
+            rows,
+            scheduler,
+            current_grid,
+            time_budget,
+            log,
+            cancel,
+            stats,
+        }
+    }
+
+    /// Every row is complete or the generation was cancelled, either way no more slices are due
+    fn is_done(&self) -> bool {
+        self.remaining == 0 || self.stats.cancelled
+    }
+
+    /// Gives the row the scheduler picks one time slice, recorded in the stats and, when sharing progress, copied into it
+    /// Gives up instead if `cancel` is set, it is checked before every slice
+    async fn run_slice(&mut self) {
+        if self.cancel.is_cancelled() {
+            self.stats.cancelled = true;
+            return;
+        }
+
+        let i = self.scheduler.next_row(&self.unfinished);
+        let log = &self.log;
+        let slice_start = log.started.elapsed();
+        let outcome = self.row_coroutines[i](self.current_grid.clone(), self.time_budget, log.progress.is_some()).await;
+        let slice_end = log.started.elapsed();
+        if let Some(progress) = &log.progress {
+            progress.record(self.rows[i].row, &outcome);
+        }
+
+        let stats = &mut self.stats;
+        let row = &mut stats.rows[i];
+        row.slices += 1;
+        row.busy += slice_end - slice_start;
+        if outcome.is_complete {
+            row.finished_at = slice_end;
+        } else {
+            row.expired += 1;
+        }
+        if stats.slices.len() < MAX_RECORDED_SLICES {
+            stats.slices.push(SliceRecord {
+                row       : row.row,
+                band      : log.band,
+                start     : slice_start,
+                duration  : slice_end - slice_start,
+                start_col : outcome.start_col,
+                end_col   : outcome.end_col,
+                completed : outcome.is_complete,
+            });
+        } else {
+            stats.dropped_slices += 1;
+        }
+
+        if outcome.is_complete {
+            self.unfinished[i] = false;
+            self.results[i] = outcome.result.expect("a complete slice hands back its row");
+            self.remaining -= 1;
+        }
+    }
+
+    /// Keeps giving the band slices until it is done
+    async fn drive(mut self) -> FinishedBand {
+        while !self.is_done() {
+            self.run_slice().await;
+        }
+        self.finish()
+    }
+
+    fn finish(self) -> FinishedBand {
+        let results = (!self.stats.cancelled).then_some(self.results);
+        (self.row_coroutines, results, self.stats)
+    }
+}
+
+/// Drives every band on tokio's blocking pool at once, waiting for them in order
+/// Every band is awaited even after one is cancelled, the coroutines have to come back
+async fn drive_on_workers(bands: Vec<BandRun>) -> Vec<FinishedBand> {
+    let runtime = tokio::runtime::Handle::current();
+    let workers: Vec<_> = bands.into_iter()
+        .map(|band| {
+            let runtime = runtime.clone();
+            tokio::task::spawn_blocking(move || runtime.block_on(band.drive()))
+        })
+        .collect();
+
+    let mut finished = Vec::with_capacity(workers.len());
+    for worker in workers {
+        finished.push(worker.await.expect("row band worker panicked"));
+    }
+    finished
+}
+
+/// One scheduler for the row work of every processor given it, interleaving their bands on one tokio runtime
+/// Each driver takes the band at the front of one queue, gives it a slice and puts it back at the end,
+/// so the rows of every universe get slices in turn while each band still picks its own rows by its policy
+/// With more than one driver the bands run in parallel too, the drivers run until the runtime shuts down
+#[derive(Clone)]
+pub struct SharedScheduler {
+    queue: Arc<SharedQueue>,
+}
+
+struct SharedQueue {
+    bands : Mutex<VecDeque<QueuedBand>>,
+    ready : Notify,                         // Wakes a driver when a band is queued
+}
+
+/// A band waiting for its next slice, and where to send it once it is done
+struct QueuedBand {
+    run   : BandRun,
+    reply : oneshot::Sender<FinishedBand>,
+}
+
+impl SharedScheduler {
+    /// Starts `drivers` driver tasks on `runtime`, at least one
+    pub fn new(runtime: &tokio::runtime::Handle, drivers: usize) -> Self {
+        let queue = Arc::new(SharedQueue { bands: Mutex::default(), ready: Notify::new() });
+        for _ in 0..drivers.max(1) {
+            runtime.spawn(drive_queue(queue.clone()));
+        }
+        Self { queue }
+    }
+
+    /// Queues every band and waits for all of them to come back, in the order given
+    /// A band lost because the runtime shut down comes back cancelled and without its coroutines
+    async fn run(&self, bands: Vec<BandRun>) -> Vec<FinishedBand> {
+        let replies: Vec<_> = bands.into_iter()
+            .map(|run| {
+                let (reply, finished) = oneshot::channel();
+                self.queue.push(QueuedBand { run, reply });
+                finished
+            })
+            .collect();
+
+        let mut finished = Vec::with_capacity(replies.len());
+        for reply in replies {
+            finished.push(reply.await.unwrap_or_else(|_| (Vec::new(), None, GenerationStats { cancelled: true, ..GenerationStats::default() })));
+        }
+        finished
+    }
+}
+
+impl SharedQueue {
+    fn push(&self, band: QueuedBand) {
+        self.bands.lock().unwrap().push_back(band);
+        self.ready.notify_one();
+    }
+}
+
+/// A driver of the shared scheduler, gives the band at the front of the queue one slice then queues it again or hands it back
+async fn drive_queue(queue: Arc<SharedQueue>) {
+    loop {
+        let next = queue.bands.lock().unwrap().pop_front();
+        let Some(mut band) = next else {
+            queue.ready.notified().await;
+            continue;
+        };
+
+        band.run.run_slice().await;
+        if band.run.is_done() {
+            // Nobody is waiting any more if the generation was dropped, its coroutines go with it
+            let _ = band.reply.send(band.run.finish());
+        } else {
+            queue.push(band);
+        }
+    }
+}
+
+/// Fresh coroutines for the active rows only (GRID_START..=height)
+fn create_row_coroutines(width: usize, height: usize, rule: Rule, boundary: Boundary) -> Vec<RowCoroutine> {
+    (GRID_START..height + 1)
+        .map(|row| Box::new(create_time_sliced_row_coroutine(row, width, rule, boundary)) as RowCoroutine)
+        .collect()
+}
+
+/// Generation processor that manages time-sliced closure-based coroutines
+/// With more than one thread the rows are split into contiguous bands, each driven on tokio's blocking pool
+/// Given a shared scheduler, the bands are driven by it instead, interleaved with every other processor's
+/// The scheduling policy decides which unfinished row gets the next slice
+pub struct GenerationProcessor {
+    row_coroutines: Vec<RowCoroutine>,
+    time_budget_per_slice: Duration,
+    width: usize,
+    height: usize,
+    rule: Rule,
+    boundary: Boundary,
+    threads: usize,     // Row bands processed in parallel, 1 keeps everything on the calling task
+    policy: SchedulingPolicy,
+    deadline: Duration, // Time the whole generation should take, spread over the rows top to bottom
+    last_stats: Arc<GenerationStats>,  // Slices of the most recent generation
+    progress: Option<Arc<PartialGeneration>>,  // Shared while progressive rendering is on
+    in_flight: bool,    // Set while a generation runs, still set on the next call if it was dropped part way
+    shared: Option<SharedScheduler>,  // Drives the bands alongside other processors' when set
+}
+
+impl GenerationProcessor {
+    pub fn new(width: usize, height: usize, rule: Rule, boundary: Boundary, time_budget_per_slice: Duration) -> Self {
+        Self {
+            row_coroutines: create_row_coroutines(width, height, rule, boundary),
+            time_budget_per_slice,
+            width,
+            height,
+            rule,
+            boundary,
+            threads: 1,
+            policy: SchedulingPolicy::default(),
+            deadline: DEFAULT_DEADLINE,
+            last_stats: Arc::default(),
+            progress: None,
+            in_flight: false,
+            shared: None,
+        }
+    }
+
+    /// Rebuilds the row coroutines, keeping the thread count and scheduling settings
+    fn rebuild(&mut self, width: usize, height: usize, rule: Rule, boundary: Boundary) {
+        *self = Self {
+            threads: self.threads,
+            policy: self.policy,
+            deadline: self.deadline,
+            progress: self.progress.take(),
+            shared: self.shared.take(),
+            ..Self::new(width, height, rule, boundary, self.time_budget_per_slice)
+        };
+    }
+
+    /// Rebuilds the row coroutines for a new active area
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.rebuild(width, height, self.rule, self.boundary);
+    }
+
+    /// Rebuilds the row coroutines with a different rule baked in
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rebuild(self.width, self.height, rule, self.boundary);
+    }
+
+    /// Rebuilds the row coroutines with neighbor positions wrapped for `boundary`
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.rebuild(self.width, self.height, self.rule, boundary);
+    }
+
+    /// Computes the next generation, rows that run out of budget yield to the executor
+    /// Needs a tokio runtime when using more than one thread; spawn it to keep the caller free meanwhile
+    pub async fn process_generation(&mut self, current_grid: Arc<TGrid>) -> TGrid {
+        self.process_generation_cancellable(current_grid, &CancelToken::default()).await
+            .expect("generation cancelled without a cancel token")
+    }
+
+    /// Like `process_generation`, but gives up between slices once `cancel` is set
+    /// A cancelled generation returns `None` and leaves every row coroutine as if freshly created,
+    /// and so does one whose future was dropped, from the next call on
+    pub async fn process_generation_cancellable(&mut self, current_grid: Arc<TGrid>, cancel: &CancelToken) -> Option<TGrid> {
+        debug_assert_eq!((current_grid.width(), current_grid.height()), (self.width, self.height));
+
+        // The last generation was abandoned mid-row, its columns and partial rows are still in the coroutines,
+        // or its bands were still out when it was dropped and never brought them back
+        if self.in_flight || self.row_coroutines.len() != self.height {
+            self.reset();
+        }
+        self.in_flight = true;
+
+        let log = SliceLog { band: 0, started: Instant::now(), progress: self.progress.clone() };
+        if let Some(progress) = &log.progress {
+            progress.begin(self.width, self.height);
+        }
+
+        let rows = RowInfo::for_grid(&current_grid, self.deadline);
+        let mut bands = self.split_bands(&current_grid, rows, &log, cancel);
+        let finished = match &self.shared {
+            Some(shared) => shared.run(bands).await,
+            None if bands.len() == 1 => vec![bands.pop().expect("one band").drive().await],
+            None => drive_on_workers(bands).await,
+        };
+        let (results, mut stats) = self.rejoin(finished);
+        stats.wall_time = log.started.elapsed();
+        self.last_stats = Arc::new(stats);
+        self.in_flight = false;
+        if let Some(progress) = &log.progress {
+            progress.end();
+        }
+
+        match results {
+            // Collect results into new grid
+            Some(results) => Some(self.collect_results(results)),
+            None => {
+                self.reset();
+                None
+            }
+        }
+    }
+
+    /// Recreates every row coroutine, dropping whatever an abandoned generation left in them
+    pub fn reset(&mut self) {
+        self.row_coroutines = create_row_coroutines(self.width, self.height, self.rule, self.boundary);
+    }
+
+    /// Moves the row coroutines out in one contiguous band per thread, each with its own scheduler
+    fn split_bands(&mut self, current_grid: &Arc<TGrid>, rows: Vec<RowInfo>, log: &SliceLog, cancel: &CancelToken) -> Vec<BandRun> {
+        let band_size = self.height.div_ceil(self.threads);
+        let mut row_coroutines = std::mem::take(&mut self.row_coroutines).into_iter();
+        rows.chunks(band_size)
+            .enumerate()
+            .map(|(band, rows)| BandRun::new(
+                row_coroutines.by_ref().take(rows.len()).collect(),
+                rows.to_vec(),
+                self.policy.create_scheduler(),
+                current_grid.clone(),
+                self.time_budget_per_slice,
+                SliceLog { band, ..log.clone() },
+                cancel.clone(),
+            ))
+            .collect()
+    }
+
+    /// Puts the bands' coroutines back in row order and joins their rows and slices
+    /// Every row is computed exactly as it would be in one band, so the result is bit-identical
+    fn rejoin(&mut self, finished: Vec<FinishedBand>) -> (Option<Vec<TRow>>, GenerationStats) {
+        let mut results = Some(Vec::with_capacity(self.height));
+        let mut stats = GenerationStats::default();
+        for (band, band_results, band_stats) in finished {
+            self.row_coroutines.extend(band);
+            match (&mut results, band_results) {
+                (Some(results), Some(band_results)) => results.extend(band_results),
+                _ => results = None,
+            }
+            stats.rows.extend(band_stats.rows);
+            stats.slices.extend(band_stats.slices);
+            stats.dropped_slices += band_stats.dropped_slices;
+            stats.cancelled |= band_stats.cancelled;
+        }
+        stats.slices.sort_by_key(|slice| slice.start);
+        (results, stats)
+    }
+
+    fn collect_results(&self, results: Vec<TRow>) -> TGrid {
+        let mut next_grid = TGrid::new(self.width, self.height);
+        for (i, row_result) in results.iter().enumerate() {
+            let row_index = i + GRID_START;  // Map back to active range (GRID_START..=height)
+            next_grid[row_index].copy_from_slice(row_result);
+        }
+        next_grid
+    }
+
+    /// Per-row and per-slice timings of the most recent generation
+    pub fn last_stats(&self) -> Arc<GenerationStats> {
+        self.last_stats.clone()
+    }
+
+    /// Shares each generation as it is computed, see `progress`
+    pub fn set_progressive(&mut self, progressive: bool) {
+        self.progress = progressive.then(|| self.progress.take().unwrap_or_default());
+    }
+
+    /// The generation being computed, while progressive rendering is on
+    pub fn progress(&self) -> Option<Arc<PartialGeneration>> {
+        self.progress.clone()
+    }
+
+    pub fn set_time_budget(&mut self, new_budget: Duration) {
+        self.time_budget_per_slice = new_budget;
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Number of row bands computed in parallel, at least 1
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Hands the bands to `shared` from the next generation on, or drives them itself again with `None`
+    pub fn set_shared_scheduler(&mut self, shared: Option<SharedScheduler>) {
+        self.shared = shared;
+    }
+
+    pub fn policy(&self) -> SchedulingPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: SchedulingPolicy) {
+        self.policy = policy;
+    }
+
+    /// How long a generation should take, only the earliest-deadline-first policy looks at it
+    pub fn set_deadline(&mut self, deadline: Duration) {
+        self.deadline = deadline;
+    }
+}
@@ -0,0 +1,250 @@
+// cycle.rs - Grid hashing and exact cycle detection
+// Brent's algorithm finds the period from hashes, a matching hash is confirmed against a stored copy of the cells
+// The start is found walking back through remembered hashes, populations have to match too so a collision can't move it
+
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::grid::TGrid;
+
+/// Generations whose hashes and populations are kept to find where a cycle began, 6 MB at most
+const HISTORY_LIMIT: usize = 1 << 18;
+
+/// Hash of the active area only, the dead border is ignored
+pub fn hash_grid(grid: &TGrid) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (grid.width(), grid.height()).hash(&mut hasher);
+    for row in grid.rows() {
+        grid[row][grid.cols()].hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A cycle the generations have entered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub period      : u64,     // Generations between repeats, 1 for a still life
+    pub start       : u64,     // First generation on the cycle
+    pub start_exact : bool,    // False if it began before the oldest remembered generation, `start` is then that one
+    pub detected_at : u64,     // Generation that closed the cycle
+}
+
+impl Cycle {
+    /// The same cycle counted from `generations` earlier, for callers whose generation count runs ahead of the detector's
+    pub fn shifted(self, generations: u64) -> Self {
+        Self { start: self.start + generations, detected_at: self.detected_at + generations, ..self }
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = if self.start_exact { "at" } else { "at or before" };
+        write!(f, "entered period-{} cycle {} gen {}", self.period, at, self.start)
+    }
+}
+
+/// Brent's cycle detection over the generations as they are computed
+/// `S` is whatever compares two generations exactly, the tortoise keeps a copy of it at each power-of-two checkpoint
+/// Every generation must be fed in order, reset after any edit to the cells
+/// Stepping several generations at a time finds the least common multiple of the step and the period
+pub struct CycleDetector<S = TGrid> {
+    tortoise : Option<(u64, u64, S)>,     // Generation, hash and state at the last checkpoint
+    power    : u64,                       // Observations from one checkpoint to the next
+    lambda   : u64,                       // Observations since the checkpoint
+    history  : VecDeque<(u64, u64, u64)>, // Generation, hash and population of the most recent observations, oldest first
+    trimmed  : bool,                      // History has lost its oldest entries
+    found    : Option<Cycle>,
+}
+
+impl<S> Default for CycleDetector<S> {
+    fn default() -> Self {
+        Self { tortoise: None, power: 1, lambda: 0, history: VecDeque::new(), trimmed: false, found: None }
+    }
+}
+
+impl<S: Clone + PartialEq> CycleDetector<S> {
+    /// Records `generation` with its live cell count, returning the cycle once a repeat has been confirmed cell for cell
+    /// Once found the cycle is returned for every later generation until `reset`
+    pub fn observe(&mut self, generation: u64, hash: u64, population: u64, state: &S) -> Option<Cycle> {
+        if self.found.is_some() {
+            return self.found;
+        }
+
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+            self.trimmed = true;
+        }
+        self.history.push_back((generation, hash, population));
+
+        let Some((tortoise_generation, tortoise_hash, tortoise_state)) = &self.tortoise else {
+            self.tortoise = Some((generation, hash, state.clone()));
+            return None;
+        };
+
+        self.lambda += 1;
+        if *tortoise_hash == hash && tortoise_state == state {
+            let period = generation - tortoise_generation;
+            self.found = Some(self.locate_start(period, generation));
+            return self.found;
+        }
+
+        // The hare has gone a full power past the tortoise without meeting it, move the checkpoint up
+        if self.lambda == self.power {
+            self.tortoise = Some((generation, hash, state.clone()));
+            self.power *= 2;
+            self.lambda = 0;
+        }
+        None
+    }
+
+    /// Walks back from the confirmed repeat while each remembered generation matches the one a period later
+    /// Only hashes and populations are remembered, both must match for an earlier generation to count as on the cycle
+    fn locate_start(&self, period: u64, generation: u64) -> Cycle {
+        let lambda = self.lambda as usize;
+        let newest = self.history.len() - 1;
+        let Some(mut first) = newest.checked_sub(lambda) else {
+            return Cycle { period, start: generation - period, start_exact: false, detected_at: generation };
+        };
+
+        let same = |(_, hash, population): (u64, u64, u64), (_, later_hash, later_population): (u64, u64, u64)| {
+            hash == later_hash && population == later_population
+        };
+        while first > 0 && same(self.history[first - 1], self.history[first - 1 + lambda]) {
+            first -= 1;
+        }
+        Cycle { period, start: self.history[first].0, start_exact: first > 0 || !self.trimmed, detected_at: generation }
+    }
+
+    /// The cycle found so far, if any
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.found
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::closure_engine::ClosureEngine;
+    use crate::patterns;
+    use crate::rules::Rule;
+    use crate::topology::Boundary;
+    use crate::test_support::grid_with;
+    use crate::GridEngine;
+
+    /// Steps `grid` until the detector reports a cycle, returning it with every generation seen
+    fn detect(mut grid: TGrid, boundary: Boundary, limit: u64) -> (Cycle, Vec<TGrid>) {
+        let mut engine = ClosureEngine::new(grid.width(), grid.height(), Rule::default(), boundary);
+        let mut detector = CycleDetector::default();
+        let mut seen = Vec::new();
+        for generation in 0..=limit {
+            if generation > 0 {
+                engine.step(&mut grid);
+            }
+            seen.push(grid.clone());
+            if let Some(cycle) = detector.observe(generation, hash_grid(&grid), grid.count_live_cells() as u64, &grid) {
+                return (cycle, seen);
+            }
+        }
+        panic!("no cycle within {} generations", limit);
+    }
+
+    /// The least period and first generation on it, found by comparing every stored grid
+    fn brute_force(seen: &[TGrid]) -> (u64, u64) {
+        for start in 0..seen.len() {
+            if let Some(period) = (start + 1..seen.len()).find(|&later| seen[later] == seen[start]).map(|later| later - start) {
+                return (period as u64, start as u64);
+            }
+        }
+        panic!("the generations seen never repeat");
+    }
+
+    #[test]
+    fn blinker_has_period_two_from_the_start() {
+        let (cycle, _) = detect(grid_with(5, 5, &[(3, 2), (3, 3), (3, 4)]), Boundary::DeadBorder, 100);
+        assert_eq!((cycle.period, cycle.start, cycle.start_exact), (2, 0, true));
+        assert_eq!(cycle.detected_at, 3);
+    }
+
+    #[test]
+    fn still_life_has_period_one() {
+        let (cycle, _) = detect(grid_with(4, 4, &[(2, 2), (2, 3), (3, 2), (3, 3)]), Boundary::DeadBorder, 100);
+        assert_eq!((cycle.period, cycle.start), (1, 0));
+    }
+
+    #[test]
+    fn glider_on_a_torus_returns_after_crossing_it() {
+        // One cell diagonally every four generations, eight cells to get round an 8x8 torus
+        let glider = grid_with(8, 8, &[(1, 2), (2, 3), (3, 1), (3, 2), (3, 3)]);
+        let (cycle, seen) = detect(glider, Boundary::Torus, 1000);
+        assert_eq!((cycle.period, cycle.start, cycle.start_exact), (32, 0, true));
+        assert_eq!(brute_force(&seen), (32, 0));
+    }
+
+    #[test]
+    fn transient_before_the_cycle_is_not_counted() {
+        // A T-tetromino grows into a traffic light, four blinkers, after a few generations of change
+        let t_tetromino = grid_with(20, 20, &[(10, 9), (10, 10), (10, 11), (11, 10)]);
+        let (cycle, seen) = detect(t_tetromino, Boundary::DeadBorder, 1000);
+        assert_eq!(cycle.period, 2);
+        assert!(cycle.start > 0 && cycle.start_exact);
+        assert_eq!(brute_force(&seen), (cycle.period, cycle.start));
+    }
+
+    #[test]
+    fn random_soups_match_a_brute_force_search() {
+        for seed in 0..8 {
+            let mut soup = TGrid::new(12, 12);
+            patterns::apply_random_pattern(&mut soup, seed);
+            let (cycle, seen) = detect(soup, Boundary::Torus, 5000);
+            assert_eq!((cycle.period, cycle.start), brute_force(&seen), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn found_cycle_is_kept_until_reset() {
+        let mut detector = CycleDetector::<u64>::default();
+        let states = [1, 2, 1, 2];
+        let found: Vec<_> = states.iter().enumerate().map(|(g, &state)| detector.observe(g as u64, state, 1, &state)).collect();
+        assert_eq!(found[3].map(|cycle| (cycle.period, cycle.start)), Some((2, 0)));
+        assert_eq!(detector.observe(4, 99, 1, &99), found[3]);
+
+        detector.reset();
+        assert_eq!(detector.cycle(), None);
+        assert_eq!(detector.observe(0, 5, 1, &5), None);
+    }
+
+    #[test]
+    fn hash_collision_does_not_report_a_false_cycle() {
+        // Every state hashes alike, only the stored state tells them apart
+        let mut detector = CycleDetector::<u64>::default();
+        for generation in 0..100 {
+            assert_eq!(detector.observe(generation, 0, generation, &generation), None);
+        }
+    }
+
+    #[test]
+    fn hash_collision_before_the_cycle_does_not_move_its_start() {
+        // States 10, 11 lead into the cycle 20, 21, 20, 21, ...  and 11 collides with 21 in hash
+        // Its population differs, so generation 1 is not mistaken for part of the cycle
+        let states = [(10, 1, 5), (11, 2, 6), (20, 3, 7), (21, 2, 8), (20, 3, 7), (21, 2, 8), (20, 3, 7), (21, 2, 8)];
+        let mut detector = CycleDetector::<u64>::default();
+        let cycle = states.iter()
+            .enumerate()
+            .find_map(|(g, &(state, hash, population))| detector.observe(g as u64, hash, population, &state))
+            .expect("no cycle found");
+        assert_eq!((cycle.period, cycle.start), (2, 2));
+    }
+
+    #[test]
+    fn shifted_moves_start_and_detection() {
+        let cycle = Cycle { period: 2, start: 3, start_exact: true, detected_at: 7 };
+        assert_eq!(cycle.shifted(10), Cycle { period: 2, start: 13, start_exact: true, detected_at: 17 });
+        assert_eq!(cycle.to_string(), "entered period-2 cycle at gen 3");
+    }
+}
@@ -0,0 +1,252 @@
+// future_engine.rs - Row coroutines as hand-written futures polled by a minimal executor
+// Same time-sliced rows as coroutine_engine, without tokio, boxed closures or a boxed future per slice
+// Each row is an explicit state machine that remembers its column between polls
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::grid::{TGrid, TRow, GRID_START};
+use crate::rules::Rule;
+use crate::scheduler::{RowInfo, Scheduler, SchedulingPolicy};
+use crate::topology::Boundary;
+use crate::GridEngine;
+
+/// One row of the next generation, computed a time slice per poll
+/// Ready once the row is complete, until then each poll resumes at the column the last one stopped at
+pub struct RowTask {
+    row         : usize,
+    width       : usize,
+    rule        : Rule,
+    boundary    : Boundary,
+    grid        : Option<Arc<TGrid>>,  // Generation being read, shared by every row, only held until the row is ready
+    time_budget : Duration,
+    current_col : usize,               // Next column to compute
+    result      : TRow,                // Reused from one generation to the next
+}
+
+impl RowTask {
+    pub fn new(row: usize, width: usize, rule: Rule, boundary: Boundary) -> Self {
+        Self {
+            row,
+            width,
+            rule,
+            boundary,
+            grid        : None,
+            time_budget : Duration::ZERO,
+            current_col : GRID_START,
+            result      : vec![false; width + 2],
+        }
+    }
+
+    /// Rewinds to the first column of a new generation
+    pub fn start(&mut self, grid: Arc<TGrid>, time_budget: Duration) {
+        self.grid = Some(grid);
+        self.time_budget = time_budget;
+        self.current_col = GRID_START;
+    }
+
+    pub fn current_col(&self) -> usize {
+        self.current_col
+    }
+
+    pub fn result(&self) -> &TRow {
+        &self.result
+    }
+}
+
+impl Future for RowTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let RowTask { row, width, rule, boundary, grid: held, time_budget, current_col, result } = self.get_mut();
+        let grid = held.as_deref().expect("row task polled before it was started or after it was ready");
+        let start = Instant::now();
+        let start_col = *current_col;
+        let row_end = *width + 1;
+
+        while *current_col < row_end {
+            // Check if time budget is exhausted, a slice always computes at least one cell
+            if *current_col > start_col && start.elapsed() >= *time_budget {
+                // Ready to go on straight away, the executor decides when
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let col = *current_col;
+            let mut count = 0;
+            for (nr, nc) in boundary.neighbors(*row, col, *width, grid.height()) {
+                if grid[nr][nc] { count += 1; }
+            }
+
+            result[col] = rule.next_state(grid[*row][col], count);
+            *current_col += 1;
+        }
+
+        *held = None;
+        Poll::Ready(())
+    }
+}
+
+/// Polls one task per slice until all are ready, the scheduler picks which
+/// The waker does nothing, every pending task is simply polled again when its turn comes
+/// Returns the number of slices, nothing is allocated along the way
+pub fn run_tasks(tasks: &mut [RowTask], rows: &[RowInfo], scheduler: &mut dyn Scheduler, unfinished: &mut [bool]) -> usize {
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut remaining = tasks.len();
+    let mut slices = 0;
+
+    unfinished.fill(true);
+    scheduler.start_generation(rows);
+    while remaining > 0 {
+        let i = scheduler.next_row(unfinished);
+        slices += 1;
+        if Pin::new(&mut tasks[i]).poll(&mut cx).is_ready() {
+            unfinished[i] = false;
+            remaining -= 1;
+        }
+    }
+    slices
+}
+
+/// Bounded engine driving one `RowTask` per active row on the calling thread
+pub struct FutureProcessor {
+    tasks                 : Vec<RowTask>,
+    unfinished            : Vec<bool>,     // Per task, kept between generations
+    time_budget_per_slice : Duration,
+    width                 : usize,
+    height                : usize,
+    rule                  : Rule,
+    boundary              : Boundary,
+    policy                : SchedulingPolicy,
+    last_slices           : usize,         // Slices the last generation took
+}
+
+impl FutureProcessor {
+    pub fn new(width: usize, height: usize, rule: Rule, boundary: Boundary, time_budget_per_slice: Duration) -> Self {
+        Self {
+            tasks                 : (GRID_START..height + 1).map(|row| RowTask::new(row, width, rule, boundary)).collect(),
+            unfinished            : vec![false; height],
+            time_budget_per_slice,
+            width,
+            height,
+            rule,
+            boundary,
+            policy                : SchedulingPolicy::default(),
+            last_slices           : 0,
+        }
+    }
+
+    /// Computes the next generation on the calling thread
+    pub fn process_generation(&mut self, current_grid: Arc<TGrid>) -> TGrid {
+        debug_assert_eq!((current_grid.width(), current_grid.height()), (self.width, self.height));
+
+        // Deadlines only ever order the rows top to bottom, so the generation deadline itself is irrelevant here
+        let rows = RowInfo::for_grid(&current_grid, Duration::ZERO);
+        for task in &mut self.tasks {
+            task.start(current_grid.clone(), self.time_budget_per_slice);
+        }
+
+        let mut scheduler = self.policy.create_scheduler();
+        self.last_slices = run_tasks(&mut self.tasks, &rows, scheduler.as_mut(), &mut self.unfinished);
+
+        let mut next_grid = TGrid::new(self.width, self.height);
+        for task in &self.tasks {
+            next_grid[task.row].copy_from_slice(task.result());
+        }
+        next_grid
+    }
+
+    pub fn set_time_budget(&mut self, new_budget: Duration) {
+        self.time_budget_per_slice = new_budget;
+    }
+
+    pub fn set_policy(&mut self, policy: SchedulingPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn last_slices(&self) -> usize {
+        self.last_slices
+    }
+
+    fn rebuild(&mut self, width: usize, height: usize, rule: Rule, boundary: Boundary) {
+        *self = Self { policy: self.policy, ..Self::new(width, height, rule, boundary, self.time_budget_per_slice) };
+    }
+}
+
+impl GridEngine for FutureProcessor {
+    fn step(&mut self, grid: &mut TGrid) {
+        *grid = self.process_generation(Arc::new(std::mem::take(grid)));
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.rebuild(width, height, self.rule, self.boundary);
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rebuild(self.width, self.height, rule, self.boundary);
+    }
+
+    fn set_boundary(&mut self, boundary: Boundary) {
+        self.rebuild(self.width, self.height, self.rule, boundary);
+    }
+
+    fn last_slices(&self) -> Option<usize> {
+        Some(self.last_slices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::SCHEDULING_POLICIES;
+    use crate::test_support::{assert_matches_closure_engine, closure_generations, random_grid};
+    use crate::topology::BOUNDARIES;
+
+    #[test]
+    fn matches_the_closure_engine_for_every_boundary() {
+        for &boundary in BOUNDARIES {
+            let mut processor = FutureProcessor::new(30, 20, Rule::default(), boundary, Duration::from_secs(1));
+            assert_matches_closure_engine(&mut processor, 30, 20, Rule::default(), boundary, 11, 6);
+            // A generous budget finishes each row in one slice
+            assert_eq!(processor.last_slices(), 20);
+        }
+    }
+
+    #[test]
+    fn matches_the_closure_engine_one_cell_per_slice() {
+        for &policy in SCHEDULING_POLICIES {
+            let mut processor = FutureProcessor::new(25, 15, Rule::default(), Boundary::Torus, Duration::ZERO);
+            processor.set_policy(policy);
+            assert_matches_closure_engine(&mut processor, 25, 15, Rule::default(), Boundary::Torus, 12, 6);
+            assert_eq!(processor.last_slices(), 25 * 15, "{}", policy.name());
+        }
+    }
+
+    #[test]
+    fn resized_processor_matches_the_closure_engine() {
+        let mut processor = FutureProcessor::new(10, 10, Rule::default(), Boundary::DeadBorder, Duration::ZERO);
+        processor.resize(40, 7);
+        processor.set_boundary(Boundary::KleinBottle);
+        assert_matches_closure_engine(&mut processor, 40, 7, Rule::default(), Boundary::KleinBottle, 13, 6);
+    }
+
+    #[test]
+    fn row_task_resumes_at_the_next_column() {
+        let grid = random_grid(6, 3, 14);
+        let mut task = RowTask::new(2, 6, Rule::default(), Boundary::Torus);
+        task.start(Arc::new(grid.clone()), Duration::ZERO);
+
+        let mut cx = Context::from_waker(Waker::noop());
+        for col in GRID_START..6 {
+            assert!(Pin::new(&mut task).poll(&mut cx).is_pending());
+            assert_eq!(task.current_col(), col + 1);
+        }
+        assert!(Pin::new(&mut task).poll(&mut cx).is_ready());
+
+        let expected = &closure_generations(&grid, Rule::default(), Boundary::Torus, 1)[0];
+        assert_eq!(task.result()[..], expected[2]);
+    }
+}
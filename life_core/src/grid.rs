@@ -0,0 +1,201 @@
+// grid.rs - Grid types for Conway's Game of Life
+
+use std::ops::{Index, IndexMut, Range};
+
+pub const DEFAULT_GRID_SIZE: usize = 50;              // Default active grid size (50x50 playing area)
+pub const GRID_START: usize = 1;                      // Start of active area, row/col 0 is the border
+
+pub type TRow = Vec<bool>;
+
+/// Heap-backed grid with a one-cell dead border on every side
+/// The active area is `GRID_START..=height` rows by `GRID_START..=width` columns,
+/// so `grid[row][col]` indexing works as it did for the old fixed-size array
+/// Wrapping boundaries map neighbours across the edges instead, so the border stays dead either way
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TGrid {
+    width  : usize,         // Active columns
+    height : usize,         // Active rows
+    cells  : Vec<bool>,     // (height + 2) * (width + 2), row-major, border included
+}
+
+impl Default for TGrid {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRID_SIZE, DEFAULT_GRID_SIZE)
+    }
+}
+
+impl TGrid {
+    /// An all-dead grid with a `width` x `height` active area
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "grid must be at least 1x1, got {}x{}", width, height);
+        Self {
+            width,
+            height,
+            cells: vec![false; (width + 2) * (height + 2)],
+        }
+    }
+
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    /// Row length including the two border cells
+    pub fn total_width(&self) -> usize { self.width + 2 }
+    /// Row count including the two border rows
+    pub fn total_height(&self) -> usize { self.height + 2 }
+
+    /// Number of cells in the active area
+    pub fn active_cells(&self) -> usize { self.width * self.height }
+
+    /// Active row indices
+    pub fn rows(&self) -> Range<usize> { GRID_START..self.height + 1 }
+    /// Active column indices
+    pub fn cols(&self) -> Range<usize> { GRID_START..self.width + 1 }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows().contains(&row) && self.cols().contains(&col)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(false);
+    }
+
+    /// Flips an active cell, coordinates outside the active area are ignored
+    pub fn toggle(&mut self, row: usize, col: usize) {
+        if self.contains(row, col) {
+            self[row][col] = !self[row][col];
+        }
+    }
+
+    /// Changes the active area to `width` x `height`, keeping the cells that still fit
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut resized = Self::new(width, height);
+        for row in GRID_START..=self.height.min(height) {
+            for col in GRID_START..=self.width.min(width) {
+                resized[row][col] = self[row][col];
+            }
+        }
+        *self = resized;
+    }
+
+    /// Number of live cells in the active area
+    pub fn count_live_cells(&self) -> usize {
+        self.rows()
+            .map(|row| self[row][self.cols()].iter().filter(|&&alive| alive).count())
+            .sum()
+    }
+
+    /// Panics naming the first border cell found alive, otherwise returns true
+    /// Intended for use inside `debug_assert!`
+    pub fn check_border_cells_dead(&self) -> bool {
+        let (bottom, right) = (self.height + 1, self.width + 1);
+        for col in 0..self.total_width() {
+            if self[0][col] { panic!("Top border cell [0, {}] should be false", col); }
+            if self[bottom][col] { panic!("Bottom border cell [{}, {}] should be false", bottom, col); }
+        }
+        for row in 0..self.total_height() {
+            if self[row][0] { panic!("Left border cell [{}, 0] should be false", row); }
+            if self[row][right] { panic!("Right border cell [{}, {}] should be false", row, right); }
+        }
+        true
+    }
+}
+
+impl Index<usize> for TGrid {
+    type Output = [bool];
+
+    fn index(&self, row: usize) -> &[bool] {
+        let stride = self.total_width();
+        &self.cells[row * stride..(row + 1) * stride]
+    }
+}
+
+impl IndexMut<usize> for TGrid {
+    fn index_mut(&mut self, row: usize) -> &mut [bool] {
+        let stride = self.total_width();
+        &mut self.cells[row * stride..(row + 1) * stride]
+    }
+}
+
+/// Parses a `--size` argument, either `WIDTHxHEIGHT` or a single number for a square grid
+pub fn parse_size(arg: &str) -> Option<(usize, usize)> {
+    let (width, height) = match arg.split_once(['x', 'X']) {
+        Some((width, height)) => (width.trim().parse().ok()?, height.trim().parse().ok()?),
+        None => {
+            let size = arg.trim().parse().ok()?;
+            (size, size)
+        }
+    };
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Grid size from the `--size` command line flag, or the default 50x50
+pub fn size_from_args() -> (usize, usize) {
+    std::env::args()
+        .skip_while(|arg| arg != "--size")
+        .nth(1)
+        .and_then(|arg| parse_size(&arg))
+        .unwrap_or((DEFAULT_GRID_SIZE, DEFAULT_GRID_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_grid_has_a_dead_border_round_the_active_area() {
+        let grid = TGrid::new(4, 3);
+        assert_eq!((grid.total_width(), grid.total_height()), (6, 5));
+        assert_eq!((grid.rows(), grid.cols()), (1..4, 1..5));
+        assert_eq!(grid.active_cells(), 12);
+        assert_eq!(grid.count_live_cells(), 0);
+        assert!(grid.check_border_cells_dead());
+    }
+
+    #[test]
+    fn toggle_ignores_the_border() {
+        let mut grid = TGrid::new(3, 3);
+        grid.toggle(0, 1);
+        grid.toggle(4, 4);
+        grid.toggle(2, 2);
+        assert_eq!(grid.count_live_cells(), 1);
+        assert!(grid[2][2]);
+        assert!(grid.check_border_cells_dead());
+    }
+
+    #[test]
+    fn resize_keeps_the_cells_that_still_fit() {
+        let mut grid = TGrid::new(5, 5);
+        grid.toggle(1, 1);
+        grid.toggle(2, 3);
+        grid.toggle(5, 5);
+
+        grid.resize(3, 4);
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert!(grid[1][1] && grid[2][3]);
+        assert_eq!(grid.count_live_cells(), 2);
+        assert!(grid.check_border_cells_dead());
+
+        grid.resize(8, 6);
+        assert_eq!((grid.width(), grid.height()), (8, 6));
+        assert_eq!(grid.count_live_cells(), 2);
+        assert!(grid.check_border_cells_dead());
+    }
+
+    #[test]
+    fn parse_size_accepts_width_by_height_or_a_square() {
+        assert_eq!(parse_size("80x60"), Some((80, 60)));
+        assert_eq!(parse_size("80X60"), Some((80, 60)));
+        assert_eq!(parse_size(" 80 x 60 "), Some((80, 60)));
+        assert_eq!(parse_size("100"), Some((100, 100)));
+    }
+
+    #[test]
+    fn parse_size_rejects_empty_and_zero_sizes() {
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("0"), None);
+        assert_eq!(parse_size("0x10"), None);
+        assert_eq!(parse_size("10x"), None);
+        assert_eq!(parse_size("ax10"), None);
+        assert_eq!(parse_size("-5"), None);
+    }
+}
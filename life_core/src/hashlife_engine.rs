@@ -0,0 +1,669 @@
+// hashlife_engine.rs - HashLife engine for jumping 2^k generations at a time
+// The universe is a quadtree of canonicalised nodes, identical sub-patterns share one node
+// and the result of advancing each node is memoised, so repetitive patterns run exponentially fast
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::cycle::{Cycle, CycleDetector};
+use crate::patterns::PATTERNS;
+use crate::rules::Rule;
+use crate::topology::Boundary;
+use crate::universe::{BoundingBox, Universe, UniverseLimit};
+use crate::GameOfLifeInterface;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Smallest root kept after compaction, an 8x8 square
+const MIN_ROOT_LEVEL: u8 = 3;
+/// Largest root, half its width must still fit in an i64 coordinate
+const MAX_ROOT_LEVEL: u8 = 62;
+/// Largest supported jump, leaves room for the root to grow around the pattern
+pub const MAX_STEP_LOG2: u8 = 48;
+/// Node count that triggers a garbage collection before the next step
+const GC_THRESHOLD: usize = 1 << 22;
+
+/// A square of 2^level cells, leaves are single cells and have no children
+#[derive(Clone, Copy)]
+struct Node {
+    level      : u8,
+    population : u64,
+    hash       : u64,           // Structural hash, equal for equal contents
+    children   : [NodeId; 4],   // nw, ne, sw, se
+}
+
+/// Canonical node storage plus the memoised results of advancing nodes
+struct NodeStore {
+    nodes   : Vec<Node>,
+    index   : HashMap<[NodeId; 4], NodeId>,
+    empty   : Vec<NodeId>,                      // Empty node for each level
+    results : HashMap<(NodeId, u8), NodeId>,    // (node, log2 of generations) -> centre after that many
+    rule    : Rule,
+}
+
+impl NodeStore {
+    fn new(rule: Rule) -> Self {
+        let leaf = |alive: bool| Node { level: 0, population: alive as u64, hash: alive as u64, children: [DEAD; 4] };
+        Self {
+            nodes   : vec![leaf(false), leaf(true)],
+            index   : HashMap::new(),
+            empty   : vec![DEAD],
+            results : HashMap::new(),
+            rule,
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    /// Finds or creates the node with these four children
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+
+        let level = self.node(nw).level + 1;
+        let mut hasher = DefaultHasher::new();
+        level.hash(&mut hasher);
+        let mut population = 0;
+        for child in children {
+            let child = self.node(child);
+            child.hash.hash(&mut hasher);
+            population += child.population;
+        }
+
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { level, population, hash: hasher.finish(), children });
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let id = self.join(below, below, below, below);
+            self.empty.push(id);
+        }
+        self.empty[level as usize]
+    }
+
+    /// The middle half of a node, one level down
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).children;
+        let (nw, ne, sw, se) = (self.node(nw).children[3], self.node(ne).children[2], self.node(sw).children[1], self.node(se).children[0]);
+        self.join(nw, ne, sw, se)
+    }
+
+    /// The same contents centred in a node one level up
+    fn expand(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let e = self.empty(node.level - 1);
+        let [nw, ne, sw, se] = node.children;
+        let nw = self.join(e, e, e, nw);
+        let ne = self.join(e, e, ne, e);
+        let sw = self.join(e, sw, e, e);
+        let se = self.join(se, e, e, e);
+        self.join(nw, ne, sw, se)
+    }
+
+    /// Level 2 base case: one generation of the 4x4 square gives its 2x2 centre
+    fn base_case(&mut self, id: NodeId) -> NodeId {
+        let children = self.node(id).children.map(|child| self.node(child).children);
+        let cell = |row: usize, col: usize| children[(row / 2) * 2 + col / 2][(row % 2) * 2 + col % 2] == ALIVE;
+
+        let mut next = [DEAD; 4];
+        for (i, (row, col)) in [(1, 1), (1, 2), (2, 1), (2, 2)].into_iter().enumerate() {
+            let mut count = 0;
+            for nr in row - 1..=row + 1 {
+                for nc in col - 1..=col + 1 {
+                    if (nr, nc) != (row, col) && cell(nr, nc) { count += 1; }
+                }
+            }
+            // Births need a live neighbour, empty space has to stay empty for the quadtree to work
+            let alive = cell(row, col);
+            if self.rule.next_state(alive, count) && (alive || count > 0) {
+                next[i] = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    /// Centre of a level L node after 2^step_log2 generations, needs step_log2 <= L - 2
+    fn successor(&mut self, id: NodeId, step_log2: u8) -> NodeId {
+        let node = self.node(id);
+        debug_assert!(node.level >= 2 && step_log2 <= node.level - 2);
+
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, step_log2)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.base_case(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.node(nw).children;
+            let [ne_nw, _, ne_sw, ne_se] = self.node(ne).children;
+            let [sw_nw, sw_ne, _, sw_se] = self.node(sw).children;
+            let [se_nw, se_ne, se_sw, _] = self.node(se).children;
+
+            // Nine overlapping squares of half the size
+            let squares = [
+                nw,
+                self.join(nw_ne, ne_nw, nw_se, ne_sw),
+                ne,
+                self.join(nw_sw, nw_se, sw_nw, sw_ne),
+                self.join(nw_se, ne_sw, sw_ne, se_nw),
+                self.join(ne_sw, ne_se, se_nw, se_ne),
+                sw,
+                self.join(sw_ne, se_nw, sw_se, se_sw),
+                se,
+            ];
+
+            // A full-speed step advances both stages, a smaller step only the second
+            let full_speed = step_log2 == node.level - 2;
+            let r = squares.map(|square| if full_speed { self.successor(square, node.level - 3) } else { self.centre(square) });
+
+            let second_step = if full_speed { node.level - 3 } else { step_log2 };
+            let quarters = [
+                self.join(r[0], r[1], r[3], r[4]),
+                self.join(r[1], r[2], r[4], r[5]),
+                self.join(r[3], r[4], r[6], r[7]),
+                self.join(r[4], r[5], r[7], r[8]),
+            ];
+            let [nw, ne, sw, se] = quarters.map(|quarter| self.successor(quarter, second_step));
+            self.join(nw, ne, sw, se)
+        };
+
+        self.results.insert((id, step_log2), result);
+        result
+    }
+
+    /// Copies the node tree under `id` from another store, used by garbage collection
+    fn import(&mut self, other: &NodeStore, id: NodeId, copied: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&new_id) = copied.get(&id) {
+            return new_id;
+        }
+        let [nw, ne, sw, se] = other.node(id).children.map(|child| self.import(other, child, copied));
+        let new_id = self.join(nw, ne, sw, se);
+        copied.insert(id, new_id);
+        new_id
+    }
+}
+
+/// HashLife universe centred on (0, 0), a root of level L covers -2^(L-1)..2^(L-1) on both axes
+pub struct HashLife {
+    store                : NodeStore,
+    root                 : NodeId,
+    generation           : u64,
+    step_log2            : u8,          // Each update advances 2^step_log2 generations
+    pub selected_pattern : usize,
+    cycle_detector       : CycleDetector<(u64, NodeId)>,   // Compared by store epoch and root, nodes are unique within a store
+    store_epoch          : u64,                             // Bumped whenever garbage collection renumbers the nodes
+    limit                : Option<UniverseLimit>,           // Why updates stopped, until the universe is cleared
+}
+
+impl HashLife {
+    pub fn new(rule: Rule) -> Self {
+        let mut store = NodeStore::new(rule);
+        let root = store.empty(MIN_ROOT_LEVEL);
+        Self {
+            store,
+            root,
+            generation       : 0,
+            step_log2        : 0,
+            selected_pattern : 0,
+            cycle_detector   : CycleDetector::default(),
+            store_epoch      : 0,
+            limit            : None,
+        }
+    }
+
+    pub fn step_log2(&self) -> u8 {
+        self.step_log2
+    }
+
+    pub fn set_step_log2(&mut self, step_log2: u8) {
+        self.step_log2 = step_log2.min(MAX_STEP_LOG2);
+    }
+
+    /// Number of canonical nodes currently stored
+    pub fn node_count(&self) -> usize {
+        self.store.nodes.len()
+    }
+
+    fn root_level(&self) -> u8 {
+        self.store.node(self.root).level
+    }
+
+    /// Half the root's width, the offset from universe to root-relative coordinates
+    fn half_width(&self) -> i64 {
+        1 << (self.root_level() - 1)
+    }
+
+    fn contains(&self, row: i64, col: i64) -> bool {
+        let half = self.half_width();
+        (-half..half).contains(&row) && (-half..half).contains(&col)
+    }
+
+    /// Shrinks the root while everything alive fits in its centre, so equal universes share a root
+    fn compact(&mut self) {
+        while self.root_level() > MIN_ROOT_LEVEL {
+            let centre = self.store.centre(self.root);
+            if self.store.node(centre).population != self.store.node(self.root).population { break; }
+            self.root = centre;
+        }
+    }
+
+    fn collect_garbage(&mut self) {
+        let mut store = NodeStore::new(self.store.rule);
+        self.root = store.import(&self.store, self.root, &mut HashMap::new());
+        self.store = store;
+        self.store_epoch += 1;
+    }
+
+    fn set_in(&mut self, id: NodeId, row: u64, col: u64, alive: bool) -> NodeId {
+        let node = self.store.node(id);
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1u64 << (node.level - 1);
+        let quadrant = (row >= half) as usize * 2 + (col >= half) as usize;
+        let mut children = node.children;
+        children[quadrant] = self.set_in(children[quadrant], row % half, col % half, alive);
+        let [nw, ne, sw, se] = children;
+        self.store.join(nw, ne, sw, se)
+    }
+
+    /// Bounds of the live cells in a node relative to its top-left corner, memoised per node
+    fn bounds_in(&self, id: NodeId, memo: &mut HashMap<NodeId, Option<[u64; 4]>>) -> Option<[u64; 4]> {
+        let node = self.store.node(id);
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some([0, 0, 0, 0]);
+        }
+        if let Some(&bounds) = memo.get(&id) {
+            return bounds;
+        }
+
+        let half = 1u64 << (node.level - 1);
+        let mut result: Option<[u64; 4]> = None;
+        for (quadrant, &child) in node.children.iter().enumerate() {
+            let (dr, dc) = ((quadrant / 2) as u64 * half, (quadrant % 2) as u64 * half);
+            if let Some([top, left, bottom, right]) = self.bounds_in(child, memo) {
+                let child_bounds = [top + dr, left + dc, bottom + dr, right + dc];
+                result = Some(match result {
+                    None => child_bounds,
+                    Some(b) => [b[0].min(child_bounds[0]), b[1].min(child_bounds[1]), b[2].max(child_bounds[2]), b[3].max(child_bounds[3])],
+                });
+            }
+        }
+        memo.insert(id, result);
+        result
+    }
+
+    /// Pushes the live cells of a node with its top-left corner at (top, left), skipping empty quadrants
+    fn cells_in(&self, id: NodeId, top: i64, left: i64, cells: &mut Vec<(i64, i64)>) {
+        let node = self.store.node(id);
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            cells.push((top, left));
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        for (quadrant, &child) in node.children.iter().enumerate() {
+            self.cells_in(child, top + (quadrant / 2) as i64 * half, left + (quadrant % 2) as i64 * half, cells);
+        }
+    }
+}
+
+impl GameOfLifeInterface for HashLife {
+    /// Does nothing once a limit is reached, the universe is left as it was and `limit` says why
+    fn update_generation(&mut self) {
+        if self.limit.is_some() { return; }
+
+        let step_log2 = self.step_log2;
+        let Some(generation) = self.generation.checked_add(1 << step_log2) else {
+            self.limit = Some(UniverseLimit::GenerationOverflow);
+            return;
+        };
+
+        if self.store.nodes.len() > GC_THRESHOLD {
+            self.collect_garbage();
+        }
+
+        // Grow until the population sits inside the centre half and the root is big enough
+        // for the jump, then once more so nothing can escape the result within 2^step_log2 generations
+        loop {
+            let level = self.root_level();
+            let centre = self.store.centre(self.root);
+            let centred = self.store.node(centre).population == self.store.node(self.root).population;
+            if level >= step_log2 + 2 && centred { break; }
+            if level >= MAX_ROOT_LEVEL {
+                self.limit = Some(UniverseLimit::Outgrown);
+                self.compact();
+                return;
+            }
+            self.root = self.store.expand(self.root);
+        }
+        self.root = self.store.expand(self.root);
+
+        self.root = self.store.successor(self.root, step_log2);
+        self.generation = generation;
+        self.compact();
+    }
+
+    /// Structural hash of the compacted root, equal universes hash alike wherever they sit in memory
+    fn hash_grid(&self) -> u64 {
+        let root = self.store.node(self.root);
+        let mut hasher = DefaultHasher::new();
+        (root.level, root.hash).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A garbage collection between two equal generations hides the repeat until the next checkpoint
+    fn check_for_cycle(&mut self) -> Option<Cycle> {
+        let current_hash = self.hash_grid();
+        self.cycle_detector.observe(self.generation, current_hash, self.population(), &(self.store_epoch, self.root))
+    }
+
+    fn clear_grid(&mut self) {
+        self.root = self.store.empty(MIN_ROOT_LEVEL);
+        self.generation = 0;
+        self.limit = None;
+        self.cycle_detector.reset();
+    }
+
+    fn apply_selected_pattern(&mut self) {
+        if let Some(pattern) = PATTERNS.get(self.selected_pattern) {
+            self.apply_pattern(pattern);
+        }
+    }
+
+    /// Memoised results depend on the rule, so they are thrown away
+    fn set_rule(&mut self, rule: Rule) {
+        self.store.rule = rule;
+        self.store.results.clear();
+        self.cycle_detector.reset();
+    }
+
+    /// The universe has no edges, so every boundary behaves the same
+    fn set_boundary(&mut self, _boundary: Boundary) {}
+
+    /// There is no border to go live
+    fn check_border_cells_dead(&self) -> bool {
+        true
+    }
+}
+
+impl Universe for HashLife {
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.store.node(self.root).population
+    }
+
+    fn is_alive(&self, row: i64, col: i64) -> bool {
+        if !self.contains(row, col) {
+            return false;
+        }
+        let half = self.half_width();
+        let (mut row, mut col) = ((row + half) as u64, (col + half) as u64);
+        let mut node = self.store.node(self.root);
+        while node.level > 0 {
+            let half = 1u64 << (node.level - 1);
+            let quadrant = (row >= half) as usize * 2 + (col >= half) as usize;
+            (row, col) = (row % half, col % half);
+            node = self.store.node(node.children[quadrant]);
+        }
+        node.population == 1
+    }
+
+    /// A cell beyond the largest root cannot be stored, setting one alive stops the universe as outgrown instead
+    fn set_cell(&mut self, row: i64, col: i64, alive: bool) {
+        self.cycle_detector.reset();
+        while !self.contains(row, col) {
+            if self.root_level() >= MAX_ROOT_LEVEL {
+                if alive {
+                    self.limit = Some(UniverseLimit::Outgrown);
+                }
+                self.compact();
+                return;
+            }
+            self.root = self.store.expand(self.root);
+        }
+        let half = self.half_width();
+        self.root = self.set_in(self.root, (row + half) as u64, (col + half) as u64, alive);
+        self.compact();
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let half = self.half_width();
+        let [top, left, bottom, right] = self.bounds_in(self.root, &mut HashMap::new())?;
+        Some(BoundingBox {
+            top    : top as i64 - half,
+            left   : left as i64 - half,
+            bottom : bottom as i64 - half,
+            right  : right as i64 - half,
+        })
+    }
+
+    fn collect_live_cells(&self) -> Vec<(i64, i64)> {
+        let half = self.half_width();
+        let mut cells = Vec::with_capacity(self.population() as usize);
+        self.cells_in(self.root, -half, -half, &mut cells);
+        cells
+    }
+
+    fn step_log2(&self) -> u8 {
+        self.step_log2
+    }
+
+    fn set_step_log2(&mut self, step_log2: u8) {
+        HashLife::set_step_log2(self, step_log2);
+    }
+
+    fn limit(&self) -> Option<UniverseLimit> {
+        self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sparse_engine::{Cell, SparseLife};
+
+    const GLIDER: [Cell; 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+    const R_PENTOMINO: [Cell; 5] = [(0, 1), (0, 2), (1, 0), (1, 1), (2, 1)];
+
+    fn sorted_cells(universe: &impl Universe) -> Vec<Cell> {
+        let Some(bounds) = universe.bounding_box() else { return Vec::new() };
+        let mut cells = Vec::new();
+        for row in bounds.top..=bounds.bottom {
+            for col in bounds.left..=bounds.right {
+                if universe.is_alive(row, col) { cells.push((row, col)); }
+            }
+        }
+        cells
+    }
+
+    fn sparse_cells(sparse: &SparseLife) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = sparse.live_cells().copied().collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    fn both(cells: &[Cell], top: i64, left: i64) -> (HashLife, SparseLife) {
+        let mut hashlife = HashLife::new(Rule::default());
+        let mut sparse = SparseLife::new(Rule::default());
+        for &(row, col) in cells {
+            hashlife.set_cell(top + row, left + col, true);
+            sparse.set_cell(top + row, left + col, true);
+        }
+        (hashlife, sparse)
+    }
+
+    /// Advances HashLife 2^step_log2 generations at a time and the sparse engine one at a time, comparing after each jump
+    fn assert_jumps_match(cells: &[Cell], top: i64, left: i64, step_log2: u8, jumps: usize) {
+        let (mut hashlife, mut sparse) = both(cells, top, left);
+        hashlife.set_step_log2(step_log2);
+        for jump in 1..=jumps {
+            hashlife.update_generation();
+            for _ in 0..1u64 << step_log2 {
+                sparse.update_generation();
+            }
+            assert_eq!(hashlife.generation(), sparse.generation(), "k = {}, jump {}", step_log2, jump);
+            assert_eq!(hashlife.population(), sparse.population(), "k = {}, jump {}", step_log2, jump);
+            assert_eq!(sorted_cells(&hashlife), sparse_cells(&sparse), "k = {}, jump {}", step_log2, jump);
+        }
+    }
+
+    #[test]
+    fn single_steps_match_the_sparse_engine() {
+        assert_jumps_match(&GLIDER, 0, 0, 0, 40);
+        assert_jumps_match(&R_PENTOMINO, 0, 0, 0, 60);
+    }
+
+    #[test]
+    fn jumps_of_two_to_the_k_match_the_sparse_engine() {
+        for step_log2 in [1, 2, 3, 5, 7] {
+            assert_jumps_match(&GLIDER, 0, 0, step_log2, 3);
+            assert_jumps_match(&R_PENTOMINO, 0, 0, step_log2, 2);
+        }
+    }
+
+    #[test]
+    fn long_jump_matches_the_sparse_engine() {
+        // 1024 generations, by which point the R-pentomino has thrown off gliders in several directions
+        assert_jumps_match(&R_PENTOMINO, 0, 0, 10, 1);
+        assert_jumps_match(&GLIDER, -3, 5, 10, 1);
+    }
+
+    #[test]
+    fn jumps_cross_the_root_expansion_boundary() {
+        // The smallest root covers -4..4, these start against its edges so the first steps have to grow it
+        assert_jumps_match(&GLIDER, 1, 1, 0, 12);
+        assert_jumps_match(&GLIDER, -4, -4, 2, 6);
+        assert_jumps_match(&R_PENTOMINO, 2, -4, 4, 4);
+
+        // A jump far bigger than the root it starts from
+        let (mut hashlife, _) = both(&GLIDER, 0, 0);
+        assert_eq!(hashlife.root_level(), MIN_ROOT_LEVEL);
+        hashlife.set_step_log2(12);
+        hashlife.update_generation();
+        assert_eq!(hashlife.generation(), 4096);
+        assert_eq!(sorted_cells(&hashlife), GLIDER.map(|(row, col)| (row + 1024, col + 1024)).to_vec());
+    }
+
+    #[test]
+    fn huge_jump_moves_a_glider_exactly() {
+        let (mut hashlife, _) = both(&GLIDER, 0, 0);
+        hashlife.set_step_log2(40);
+        hashlife.update_generation();
+        let shift = 1i64 << 38;
+        assert_eq!(hashlife.generation(), 1 << 40);
+        assert_eq!(sorted_cells(&hashlife), GLIDER.map(|(row, col)| (row + shift, col + shift)).to_vec());
+    }
+
+    #[test]
+    fn collects_the_same_cells_as_probing_every_coordinate() {
+        let (mut hashlife, _) = both(&R_PENTOMINO, -7, 12);
+        hashlife.set_cell(-300, 450, true);
+        hashlife.set_step_log2(6);
+        for _ in 0..3 {
+            let mut cells = hashlife.collect_live_cells();
+            cells.sort_unstable();
+            assert_eq!(cells, sorted_cells(&hashlife));
+            assert_eq!(cells.len() as u64, hashlife.population());
+            hashlife.update_generation();
+        }
+    }
+
+    #[test]
+    fn step_size_is_capped() {
+        let mut hashlife = HashLife::new(Rule::default());
+        hashlife.set_step_log2(MAX_STEP_LOG2 + 5);
+        assert_eq!(hashlife.step_log2(), MAX_STEP_LOG2);
+    }
+
+    #[test]
+    fn compaction_shrinks_the_root_once_cells_die() {
+        let (mut hashlife, _) = both(&[(0, 0), (100, 100)], 0, 0);
+        assert!(hashlife.root_level() > MIN_ROOT_LEVEL);
+        hashlife.update_generation();
+        assert_eq!(hashlife.population(), 0);
+        assert_eq!(hashlife.root_level(), MIN_ROOT_LEVEL);
+    }
+
+    #[test]
+    fn pattern_at_the_edge_of_the_universe_stops_as_outgrown() {
+        // A block just inside the largest root cannot be centred in it, so there is no room to grow
+        let edge = (1i64 << (MAX_ROOT_LEVEL - 1)) - 2;
+        let mut hashlife = HashLife::new(Rule::default());
+        for (row, col) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            hashlife.set_cell(edge + row, col, true);
+        }
+        assert_eq!(hashlife.limit(), None);
+
+        hashlife.update_generation();
+        assert_eq!(hashlife.limit(), Some(UniverseLimit::Outgrown));
+        assert_eq!(hashlife.generation(), 0);
+        assert_eq!(hashlife.population(), 4);
+        assert!(hashlife.is_alive(edge, 0));
+
+        // Cleared, it runs again
+        hashlife.clear_grid();
+        assert_eq!(hashlife.limit(), None);
+        hashlife.update_generation();
+        assert_eq!(hashlife.generation(), 1);
+    }
+
+    #[test]
+    fn cell_beyond_the_universe_stops_as_outgrown() {
+        let mut hashlife = HashLife::new(Rule::default());
+        hashlife.set_cell(0, 0, true);
+        hashlife.set_cell(i64::MAX, 0, false);
+        assert_eq!(hashlife.limit(), None, "clearing a cell that cannot exist changes nothing");
+
+        hashlife.set_cell(i64::MAX, i64::MIN, true);
+        assert_eq!(hashlife.limit(), Some(UniverseLimit::Outgrown));
+        assert_eq!(hashlife.population(), 1);
+        assert_eq!(hashlife.root_level(), MIN_ROOT_LEVEL);
+    }
+
+    #[test]
+    fn jump_past_the_last_generation_stops_as_overflow() {
+        let mut hashlife = HashLife::new(Rule::default());
+        for &(row, col) in &GLIDER {
+            hashlife.set_cell(row, col, true);
+        }
+        hashlife.set_step_log2(MAX_STEP_LOG2);
+        hashlife.generation = u64::MAX - (1 << MAX_STEP_LOG2) - 1;
+
+        hashlife.update_generation();
+        assert_eq!(hashlife.limit(), None);
+        assert_eq!(hashlife.generation(), u64::MAX - 1);
+
+        hashlife.update_generation();
+        assert_eq!(hashlife.limit(), Some(UniverseLimit::GenerationOverflow));
+        assert_eq!(hashlife.generation(), u64::MAX - 1);
+        assert_eq!(hashlife.population(), 5);
+    }
+}
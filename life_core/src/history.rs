@@ -0,0 +1,147 @@
+// history.rs - Population and activity recorded generation by generation, for plotting and CSV export
+// Births and deaths come from comparing the grid before and after a step, engines that cannot compare leave them out
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+use crate::grid::TGrid;
+
+/// Generations kept, the oldest are dropped beyond this
+pub const HISTORY_LENGTH: usize = 10_000;
+
+/// Population and activity of one generation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Activity {
+    pub generation : u64,
+    pub population : u64,
+    pub births     : Option<u64>,      // Cells that came alive in the step to this generation, if the engine can tell
+    pub deaths     : Option<u64>,      // Cells that died in the same step
+}
+
+impl Activity {
+    /// Counts the cells of the active area that changed from `before` to `after`, which must be the same size
+    pub fn between(generation: u64, before: &TGrid, after: &TGrid) -> Self {
+        let (mut births, mut deaths) = (0, 0);
+        for row in after.rows() {
+            for col in after.cols() {
+                match (before[row][col], after[row][col]) {
+                    (false, true) => births += 1,
+                    (true, false) => deaths += 1,
+                    _ => {}
+                }
+            }
+        }
+        Self { generation, population: after.count_live_cells() as u64, births: Some(births), deaths: Some(deaths) }
+    }
+
+    /// Cells that changed state either way
+    pub fn changed(&self) -> Option<u64> {
+        Some(self.births? + self.deaths?)
+    }
+}
+
+/// Reads one count from an activity, `None` where the engine could not give it
+pub type Series = fn(&Activity) -> Option<u64>;
+
+/// The counts recorded per generation, named as in the CSV header
+pub const SERIES: [(&str, Series); 4] = [
+    ("Population", |activity| Some(activity.population)),
+    ("Births",     |activity| activity.births),
+    ("Deaths",     |activity| activity.deaths),
+    ("Changed",    Activity::changed),
+];
+
+/// The most recent `HISTORY_LENGTH` generations, oldest first
+#[derive(Clone, Debug, Default)]
+pub struct ActivityHistory {
+    samples : VecDeque<Activity>,
+}
+
+impl ActivityHistory {
+    pub fn record(&mut self, activity: Activity) {
+        if self.samples.len() == HISTORY_LENGTH {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(activity);
+    }
+
+    pub fn samples(&self) -> &VecDeque<Activity> {
+        &self.samples
+    }
+
+    /// One line per generation under a header, counts an engine could not give are left empty
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "Generation")?;
+        for (name, _) in SERIES {
+            write!(out, ",{}", name)?;
+        }
+        writeln!(out)?;
+
+        for activity in &self.samples {
+            write!(out, "{}", activity.generation)?;
+            for (_, series) in SERIES {
+                match series(activity) {
+                    Some(count) => write!(out, ",{}", count)?,
+                    None => write!(out, ",")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(history: &ActivityHistory) -> String {
+        let mut out = Vec::new();
+        history.write_csv(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_generation() {
+        let mut history = ActivityHistory::default();
+        history.record(Activity { generation: 1, population: 5, births: Some(2), deaths: Some(1) });
+        history.record(Activity { generation: 2, population: 7, births: None, deaths: None });
+
+        assert_eq!(csv(&history), "Generation,Population,Births,Deaths,Changed\n1,5,2,1,3\n2,7,,,\n");
+    }
+
+    #[test]
+    fn empty_history_is_only_the_header() {
+        assert_eq!(csv(&ActivityHistory::default()), "Generation,Population,Births,Deaths,Changed\n");
+    }
+
+    #[test]
+    fn history_keeps_the_most_recent_generations() {
+        let mut history = ActivityHistory::default();
+        for generation in 0..HISTORY_LENGTH as u64 + 5 {
+            history.record(Activity { generation, population: generation, ..Activity::default() });
+        }
+        assert_eq!(history.samples().len(), HISTORY_LENGTH);
+        assert_eq!(history.samples().front().map(|activity| activity.generation), Some(5));
+
+        let csv = csv(&history);
+        assert_eq!(csv.lines().count(), HISTORY_LENGTH + 1);
+        assert_eq!(csv.lines().nth(1), Some("5,5,,,"));
+        assert_eq!(csv.lines().last(), Some(format!("{0},{0},,,", HISTORY_LENGTH + 4).as_str()));
+    }
+
+    #[test]
+    fn births_and_deaths_between_grids() {
+        let mut before = TGrid::new(4, 4);
+        before[1][1] = true;
+        before[1][2] = true;
+        let mut after = TGrid::new(4, 4);
+        after[1][2] = true;
+        after[2][2] = true;
+        after[3][3] = true;
+
+        let activity = Activity::between(3, &before, &after);
+        assert_eq!(activity, Activity { generation: 3, population: 3, births: Some(2), deaths: Some(1) });
+        assert_eq!(activity.changed(), Some(3));
+    }
+}
@@ -0,0 +1,140 @@
+// lib.rs - Shared core of the Conway's Game of Life binaries
+// Grid types, Life-like rules, the pattern catalogue, cycle detection and the engines, with no GUI dependency
+
+pub mod grid;
+pub mod rules;
+pub mod patterns;
+pub mod cycle;
+pub mod topology;
+pub mod closure_engine;
+pub mod coroutine_engine;
+pub mod future_engine;
+pub mod cell_engine;
+pub mod scheduler;
+pub mod universe;
+pub mod sparse_engine;
+pub mod hashlife_engine;
+pub mod bitpacked_engine;
+pub mod simulation;
+pub mod cancel;
+pub mod budget;
+pub mod stream;
+pub mod classify;
+pub mod census;
+pub mod history;
+pub mod age;
+pub mod palette;
+
+#[cfg(test)]
+mod test_support;
+
+pub use grid::{TGrid, TRow, DEFAULT_GRID_SIZE, GRID_START};
+pub use patterns::{Pattern, PATTERNS};
+pub use rules::{Rule, RULES};
+pub use topology::{Boundary, BOUNDARIES};
+pub use universe::{BoundingBox, Universe, UniverseLimit};
+pub use simulation::{Command, Simulation, SimulationHandle, SimulationThread};
+pub use cancel::CancelToken;
+pub use cycle::Cycle;
+
+/// Conway's Game of Life Interface
+/// Defines the core functionality for game mechanics and pattern management
+pub trait GameOfLifeInterface {
+    // Core game mechanics
+    fn update_generation(&mut self);
+    fn hash_grid(&self) -> u64;
+    fn check_for_cycle(&mut self) -> Option<Cycle>;
+
+    // Pattern management
+    fn clear_grid(&mut self);
+    fn apply_selected_pattern(&mut self);
+
+    // Rule and topology selection
+    fn set_rule(&mut self, rule: Rule);
+    fn set_boundary(&mut self, boundary: Boundary);
+
+    // Debug utilities
+    fn check_border_cells_dead(&self) -> bool;
+}
+
+/// A bounded engine that steps a `TGrid` in place
+pub trait GridEngine: Send {
+    /// Replaces `grid` with its next generation
+    fn step(&mut self, grid: &mut TGrid);
+
+    fn resize(&mut self, width: usize, height: usize);
+    fn set_rule(&mut self, rule: Rule);
+    fn set_boundary(&mut self, boundary: Boundary);
+
+    /// Coroutine resumes the last step took, for the engines that time-slice
+    fn last_slices(&self) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashlife_engine::HashLife;
+    use crate::sparse_engine::SparseLife;
+
+    const BLINKER: [(i64, i64); 3] = [(0, -1), (0, 0), (0, 1)];
+    const GLIDER: [(i64, i64); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+    fn live_cells(universe: &impl Universe) -> Vec<(i64, i64)> {
+        let Some(bounds) = universe.bounding_box() else { return Vec::new() };
+        let mut cells = Vec::new();
+        for row in bounds.top..=bounds.bottom {
+            for col in bounds.left..=bounds.right {
+                if universe.is_alive(row, col) { cells.push((row, col)); }
+            }
+        }
+        cells
+    }
+
+    fn place(universe: &mut impl Universe, cells: &[(i64, i64)]) {
+        universe.clear_grid();
+        for &(row, col) in cells {
+            universe.set_cell(row, col, true);
+        }
+    }
+
+    fn blinker_oscillates(universe: &mut impl Universe) {
+        place(universe, &BLINKER);
+        universe.update_generation();
+        assert_eq!(live_cells(universe), vec![(-1, 0), (0, 0), (1, 0)]);
+        universe.update_generation();
+        assert_eq!(live_cells(universe), BLINKER.to_vec());
+        assert_eq!(universe.generation(), 2);
+    }
+
+    fn glider_moves_diagonally(universe: &mut impl Universe) {
+        place(universe, &GLIDER);
+        for _ in 0..4 {
+            universe.update_generation();
+        }
+        let moved: Vec<_> = GLIDER.iter().map(|&(row, col)| (row + 1, col + 1)).collect();
+        assert_eq!(live_cells(universe), moved);
+        assert_eq!(universe.population(), 5);
+    }
+
+    #[test]
+    fn sparse_blinker_oscillates() {
+        blinker_oscillates(&mut SparseLife::new(Rule::default()));
+    }
+
+    #[test]
+    fn sparse_glider_moves_diagonally() {
+        glider_moves_diagonally(&mut SparseLife::new(Rule::default()));
+    }
+
+    #[test]
+    fn hashlife_blinker_oscillates() {
+        blinker_oscillates(&mut HashLife::new(Rule::default()));
+    }
+
+    #[test]
+    fn hashlife_glider_moves_diagonally() {
+        glider_moves_diagonally(&mut HashLife::new(Rule::default()));
+    }
+}
@@ -0,0 +1,213 @@
+// palette.rs - Rendering modes that shade cells by age or recent activity, and the colour ramps they draw with
+// Colours are plain sRGB triples so the core keeps no GUI dependency, the apps turn them into their own colour type
+
+use crate::age::{CellAge, MAX_HEAT_WINDOW};
+
+/// An sRGB colour, red, green and blue
+pub type Rgb = [u8; 3];
+
+/// How cells are coloured beyond plain live and dead
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum RenderMode {
+    /// Live and dead colours only
+    #[default]
+    Plain,
+    /// Live cells along the ramp by how long they have been alive
+    Age,
+    /// Cells along the ramp by how often they changed over the last few generations
+    Heat,
+    /// Recently dead cells fade from the top of the ramp back to the dead colour
+    Trails,
+}
+
+pub const RENDER_MODES: &[RenderMode] = &[
+    RenderMode::Plain,
+    RenderMode::Age,
+    RenderMode::Heat,
+    RenderMode::Trails,
+];
+
+impl RenderMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Plain  => "Plain",
+            RenderMode::Age    => "Age",
+            RenderMode::Heat   => "Heat",
+            RenderMode::Trails => "Trails",
+        }
+    }
+
+    /// Every mode but plain needs the simulation to track cell ages
+    pub fn uses_ages(self) -> bool {
+        self != RenderMode::Plain
+    }
+}
+
+/// A gradient through three colours, from the low end of a measure to the high end
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorRamp {
+    pub low    : Rgb,
+    pub middle : Rgb,
+    pub high   : Rgb,
+}
+
+impl ColorRamp {
+    /// Colour at `position`, 0 for the low end and 1 for the high end, clamped to the ramp
+    pub fn sample(&self, position: f32) -> Rgb {
+        let position = position.clamp(0.0, 1.0) * 2.0;
+        if position <= 1.0 {
+            mix(self.low, self.middle, position)
+        } else {
+            mix(self.middle, self.high, position - 1.0)
+        }
+    }
+}
+
+/// Blends `amount` of the way from one colour to another
+pub fn mix(from: Rgb, to: Rgb, amount: f32) -> Rgb {
+    std::array::from_fn(|channel| {
+        let (from, to) = (from[channel] as f32, to[channel] as f32);
+        (from + (to - from) * amount).round() as u8
+    })
+}
+
+/// A colour ramp with a display name
+pub struct NamedRamp {
+    pub name : &'static str,
+    pub ramp : ColorRamp,
+}
+
+/// Preset ramps, each of which can still be edited colour by colour
+pub const RAMPS: &[NamedRamp] = &[
+    NamedRamp { name: "Fire",      ramp: ColorRamp { low: [120, 0, 0],  middle: [240, 120, 0],   high: [255, 240, 160] } },
+    NamedRamp { name: "Ice",       ramp: ColorRamp { low: [0, 40, 120], middle: [0, 160, 220],   high: [220, 250, 255] } },
+    NamedRamp { name: "Viridis",   ramp: ColorRamp { low: [68, 1, 84],  middle: [33, 145, 140],  high: [253, 231, 37] } },
+    NamedRamp { name: "Greyscale", ramp: ColorRamp { low: [70, 70, 70], middle: [160, 160, 160], high: [255, 255, 255] } },
+];
+
+/// A rendering mode, the ramp it draws with and how far up the ramp each measure reaches
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RenderStyle {
+    pub mode         : RenderMode,
+    pub ramp         : ColorRamp,
+    pub age_span     : u16,       // Generations alive that reach the top of the ramp
+    pub heat_window  : u32,       // Generations changes are counted over, up to `MAX_HEAT_WINDOW`
+    pub trail_length : u16,       // Generations a dead cell's trail takes to fade out
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self {
+            mode         : RenderMode::Plain,
+            ramp         : RAMPS[0].ramp,
+            age_span     : 100,
+            heat_window  : 16,
+            trail_length : 20,
+        }
+    }
+}
+
+impl RenderStyle {
+    /// Colour of a cell under this style, `None` where the plain live or dead colour is drawn
+    /// `dead` is the dead colour the trails fade into
+    pub fn shade(&self, cell: CellAge, dead: Rgb) -> Option<Rgb> {
+        match self.mode {
+            RenderMode::Plain => None,
+            RenderMode::Age => {
+                let age = cell.alive_for()?;
+                Some(self.ramp.sample(age.saturating_sub(1) as f32 / self.age_span.max(1) as f32))
+            }
+            RenderMode::Heat => {
+                let window = self.heat_window.clamp(1, MAX_HEAT_WINDOW);
+                match cell.heat(window) {
+                    0 if !cell.alive => None,
+                    heat => Some(self.ramp.sample(heat as f32 / window as f32)),
+                }
+            }
+            RenderMode::Trails => {
+                let dead_for = cell.dead_for().filter(|&dead_for| dead_for <= self.trail_length)?;
+                let fade = dead_for as f32 / (self.trail_length as f32 + 1.0);
+                Some(mix(self.ramp.sample(1.0 - fade), dead, fade))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::age::CellAges;
+    use crate::grid::{TGrid, GRID_START};
+
+    const RAMP: ColorRamp = ColorRamp { low: [0, 0, 0], middle: [100, 50, 200], high: [200, 250, 0] };
+    const DEAD: Rgb = [10, 20, 30];
+
+    /// The top-left cell after it lived for `alive` generations then was dead for `dead`
+    fn cell_after(alive: u16, dead: u16) -> CellAge {
+        let grid = |live: bool| {
+            let mut grid = TGrid::new(2, 2);
+            grid[GRID_START][GRID_START] = live;
+            grid
+        };
+        let mut ages = CellAges::new(&grid(true));
+        for _ in 1..alive {
+            ages.update(&grid(true));
+        }
+        for _ in 0..dead {
+            ages.update(&grid(false));
+        }
+        ages.get(0, 0)
+    }
+
+    fn style(mode: RenderMode) -> RenderStyle {
+        RenderStyle { mode, ramp: RAMP, age_span: 10, heat_window: 4, trail_length: 3 }
+    }
+
+    #[test]
+    fn ramp_ends_and_midpoint() {
+        assert_eq!(RAMP.sample(0.0), RAMP.low);
+        assert_eq!(RAMP.sample(0.5), RAMP.middle);
+        assert_eq!(RAMP.sample(1.0), RAMP.high);
+        assert_eq!(RAMP.sample(0.25), [50, 25, 100]);
+        assert_eq!(RAMP.sample(-1.0), RAMP.low);
+        assert_eq!(RAMP.sample(2.0), RAMP.high);
+    }
+
+    #[test]
+    fn plain_leaves_cells_to_the_live_and_dead_colours() {
+        assert_eq!(style(RenderMode::Plain).shade(cell_after(5, 0), DEAD), None);
+    }
+
+    #[test]
+    fn age_climbs_the_ramp_until_the_span() {
+        let style = style(RenderMode::Age);
+        assert_eq!(style.shade(cell_after(1, 0), DEAD), Some(RAMP.low));
+        assert_eq!(style.shade(cell_after(6, 0), DEAD), Some(RAMP.middle));
+        assert_eq!(style.shade(cell_after(11, 0), DEAD), Some(RAMP.high));
+        assert_eq!(style.shade(cell_after(50, 0), DEAD), Some(RAMP.high));
+        assert_eq!(style.shade(cell_after(5, 1), DEAD), None, "dead cells have no age");
+    }
+
+    #[test]
+    fn heat_is_the_share_of_the_window_that_changed() {
+        let style = style(RenderMode::Heat);
+        assert_eq!(style.shade(cell_after(5, 0), DEAD), Some(RAMP.low), "a settled live cell is at the bottom");
+        assert_eq!(style.shade(cell_after(5, 2), DEAD), Some(RAMP.sample(0.25)));
+        assert_eq!(style.shade(cell_after(5, 5), DEAD), None, "a settled dead cell is drawn dead");
+
+        // A window past what is kept is clamped rather than diluting the heat
+        let wide = RenderStyle { heat_window: MAX_HEAT_WINDOW * 2, ..style };
+        assert_eq!(wide.shade(cell_after(5, 2), DEAD), Some(RAMP.sample(1.0 / MAX_HEAT_WINDOW as f32)));
+    }
+
+    #[test]
+    fn trails_fade_into_the_dead_colour() {
+        let style = style(RenderMode::Trails);
+        assert_eq!(style.shade(cell_after(5, 0), DEAD), None, "live cells leave no trail");
+
+        let shades: Vec<Rgb> = (1..=3).map(|dead| style.shade(cell_after(5, dead), DEAD).expect("within the trail")).collect();
+        assert_eq!(shades[0], mix(RAMP.sample(0.75), DEAD, 0.25));
+        assert_eq!(shades[2], mix(RAMP.sample(0.25), DEAD, 0.75));
+        assert_eq!(style.shade(cell_after(5, 4), DEAD), None, "the trail ends after its length");
+    }
+}
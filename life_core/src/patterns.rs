@@ -0,0 +1,201 @@
+use crate::grid::{TGrid, DEFAULT_GRID_SIZE, GRID_START};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+pub struct Pattern {
+    pub name: &'static str,
+    pub cells: &'static [(usize, usize)],
+}
+
+pub const PATTERNS: &[Pattern] = &[
+    Pattern {
+        name: "Glider",
+        cells: &[(6, 7), (7, 8), (8, 6), (8, 7), (8, 8)],
+    },
+    Pattern {
+        name: "Blinker",
+        cells: &[(25, 24), (25, 25), (25, 26)],
+    },
+    Pattern {
+        name: "Toad",
+        cells: &[(24, 25), (24, 26), (24, 27), (25, 24), (25, 25), (25, 26)],
+    },
+    Pattern {
+        name: "Beacon",
+        cells: &[(10, 10), (10, 11), (11, 10), (11, 11), (12, 12), (12, 13), (13, 12), (13, 13)],
+    },
+    Pattern {
+        name: "Pulsar",
+        cells: &[
+            // Top section
+            (20, 24), (20, 25), (20, 26), (20, 30), (20, 31), (20, 32),
+            (22, 22), (22, 27), (22, 29), (22, 34),
+            (23, 22), (23, 27), (23, 29), (23, 34),
+            (24, 22), (24, 27), (24, 29), (24, 34),
+            (25, 24), (25, 25), (25, 26), (25, 30), (25, 31), (25, 32),
+            // Bottom section (mirrored)
+            (27, 24), (27, 25), (27, 26), (27, 30), (27, 31), (27, 32),
+            (28, 22), (28, 27), (28, 29), (28, 34),
+            (29, 22), (29, 27), (29, 29), (29, 34),
+            (30, 22), (30, 27), (30, 29), (30, 34),
+            (32, 24), (32, 25), (32, 26), (32, 30), (32, 31), (32, 32),
+        ],
+    },
+    Pattern {
+        name: "R-pentomino",
+        cells: &[(25, 25), (25, 26), (24, 26), (26, 25), (26, 24)],
+    },
+    Pattern {
+        name: "Gosper Glider Gun",
+        cells: &[
+            (5, 1), (5, 2), (6, 1), (6, 2),
+            (5, 11), (6, 11), (7, 11), (4, 12), (8, 12), (3, 13), (9, 13),
+            (3, 14), (9, 14), (6, 15), (4, 16), (8, 16), (5, 17), (6, 17),
+            (7, 17), (6, 18), (3, 21), (4, 21), (5, 21), (3, 22), (4, 22),
+            (5, 22), (2, 23), (6, 23), (1, 25), (2, 25), (6, 25), (7, 25),
+            (3, 35), (4, 35), (3, 36), (4, 36),
+        ],
+    },
+];
+
+/// Clears the grid and places `pattern` on it
+/// Catalogue coordinates are laid out on the default 50x50 grid, that layout is centred on the actual grid
+/// and the pattern moved inwards where it would cross an edge, only a pattern bigger than the grid is clipped
+pub fn apply_pattern(grid: &mut TGrid, pattern: &Pattern) {
+    // Clear grid first
+    grid.clear();
+
+    let (row_offset, col_offset) = placement(pattern, grid.width(), grid.height());
+    for &(row, col) in pattern.cells {
+        let (row, col) = (row as isize + row_offset, col as isize + col_offset);
+        if row >= 0 && col >= 0 && grid.contains(row as usize, col as usize) {
+            grid[row as usize][col as usize] = true;
+        }
+    }
+}
+
+/// Offset added to each catalogue (row, col) to place `pattern` on a `width` x `height` grid
+fn placement(pattern: &Pattern, width: usize, height: usize) -> (isize, isize) {
+    let axis = |coords: &mut dyn Iterator<Item = usize>, size: usize| {
+        let (first, last) = coords.fold((usize::MAX, 0), |(first, last), coord| (first.min(coord), last.max(coord)));
+        if first > last {
+            return 0;
+        }
+
+        // Where the 50x50 layout puts it once centred, kept inside the active area where it fits
+        let centred = (size as isize - DEFAULT_GRID_SIZE as isize) / 2;
+        let (lowest, highest) = (GRID_START as isize - first as isize, size as isize - last as isize);
+        if lowest <= highest { centred.clamp(lowest, highest) } else { (lowest + highest) / 2 }
+    };
+
+    (
+        axis(&mut pattern.cells.iter().map(|&(row, _)| row), height),
+        axis(&mut pattern.cells.iter().map(|&(_, col)| col), width),
+    )
+}
+
+pub fn apply_random_pattern(grid: &mut TGrid, seed_value: u32) {
+    // Clear everything first
+    grid.clear();
+
+    // Simple pseudo-random generator
+    let mut hasher = DefaultHasher::new();
+    seed_value.hash(&mut hasher);
+    let mut seed = hasher.finish();
+
+    // Only fill the active area
+    let cols = grid.cols();
+    for row in grid.rows() {
+        for cell in &mut grid[row][cols.clone()] {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            *cell = seed.is_multiple_of(3); // ~33% chance of being alive
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(name: &str) -> &'static Pattern {
+        PATTERNS.iter().find(|pattern| pattern.name == name).unwrap()
+    }
+
+    #[test]
+    fn apply_pattern_replaces_the_grid() {
+        let mut grid = TGrid::default();
+        apply_random_pattern(&mut grid, 7);
+
+        let blinker = pattern("Blinker");
+        apply_pattern(&mut grid, blinker);
+        assert_eq!(grid.count_live_cells(), blinker.cells.len());
+    }
+
+    fn live_cells(grid: &TGrid) -> Vec<(usize, usize)> {
+        grid.rows().flat_map(|row| grid.cols().map(move |col| (row, col))).filter(|&(row, col)| grid[row][col]).collect()
+    }
+
+    fn shifted(pattern: &Pattern, rows: isize, cols: isize) -> Vec<(usize, usize)> {
+        let mut cells: Vec<_> = pattern.cells.iter()
+            .map(|&(row, col)| ((row as isize + rows) as usize, (col as isize + cols) as usize))
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn default_grid_uses_catalogue_coordinates() {
+        let mut grid = TGrid::default();
+        for pattern in PATTERNS {
+            apply_pattern(&mut grid, pattern);
+            assert_eq!(live_cells(&grid), shifted(pattern, 0, 0), "{}", pattern.name);
+        }
+    }
+
+    #[test]
+    fn larger_grid_centres_the_catalogue_layout() {
+        let mut grid = TGrid::new(120, 80);
+        for pattern in PATTERNS {
+            apply_pattern(&mut grid, pattern);
+            assert_eq!(live_cells(&grid), shifted(pattern, 15, 35), "{}", pattern.name);
+        }
+    }
+
+    #[test]
+    fn smaller_grid_moves_patterns_inside() {
+        let mut grid = TGrid::new(20, 16);
+
+        // Centring the 50x50 layout would put the glider off the top-left, it is pushed back to the corner
+        apply_pattern(&mut grid, pattern("Glider"));
+        assert_eq!(live_cells(&grid), shifted(pattern("Glider"), -5, -5));
+
+        // The blinker sits in the middle of the layout, so it stays in the middle of the grid
+        apply_pattern(&mut grid, pattern("Blinker"));
+        assert_eq!(live_cells(&grid), shifted(pattern("Blinker"), -17, -15));
+    }
+
+    #[test]
+    fn pattern_bigger_than_the_grid_is_centred_and_clipped() {
+        let gun = pattern("Gosper Glider Gun");
+        let mut grid = TGrid::new(30, 30);
+        apply_pattern(&mut grid, gun);
+        assert!(grid.count_live_cells() < gun.cells.len());
+        assert!(grid.check_border_cells_dead());
+
+        // Its full height still fits, so no rows are lost
+        let rows: std::collections::BTreeSet<_> = live_cells(&grid).into_iter().map(|(row, _)| row).collect();
+        assert_eq!(rows.len(), 9);
+    }
+
+    #[test]
+    fn random_pattern_only_fills_the_active_area() {
+        let mut grid = TGrid::new(20, 10);
+        apply_random_pattern(&mut grid, 1);
+        assert!(grid.count_live_cells() > 0);
+        assert!(grid.check_border_cells_dead());
+
+        let mut again = TGrid::new(20, 10);
+        apply_random_pattern(&mut again, 1);
+        assert_eq!(grid, again);
+    }
+}
@@ -0,0 +1,188 @@
+// rules.rs - Life-like rules in B/S rulestring notation
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A Life-like rule: which neighbour counts give birth to a dead cell and which keep a live cell alive
+/// Bit `n` of each mask is set when a count of `n` (0-8) applies
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rule {
+    birth    : u16,
+    survival : u16,
+}
+
+pub const CONWAY: Rule = Rule::new(&[3], &[2, 3]);
+
+impl Default for Rule {
+    fn default() -> Self { CONWAY }
+}
+
+impl Rule {
+    /// Builds a rule from its birth and survival neighbour counts
+    pub const fn new(birth: &[u8], survival: &[u8]) -> Self {
+        Self { birth: mask(birth), survival: mask(survival) }
+    }
+
+    /// Applies the rule to one cell given its live neighbour count
+    pub fn next_state(&self, alive: bool, count: u8) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask & (1 << count) != 0
+    }
+}
+
+const fn mask(counts: &[u8]) -> u16 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < counts.len() {
+        assert!(counts[i] <= 8, "neighbour counts run from 0 to 8");
+        mask |= 1 << counts[i];
+        i += 1;
+    }
+    mask
+}
+
+/// Error returned when a rulestring can't be parsed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseRuleError {
+    pub rulestring : String,
+    pub reason     : &'static str,
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rule \"{}\": {}", self.rulestring, self.reason)
+    }
+}
+
+impl std::error::Error for ParseRuleError {}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Accepts `B3/S23` style (either order, any case) and the older `23/3` survival/birth form
+    fn from_str(rulestring: &str) -> Result<Self, Self::Err> {
+        let error = |reason| ParseRuleError { rulestring: rulestring.to_string(), reason };
+
+        let (first, second) = rulestring.trim().split_once('/').ok_or_else(|| error("expected a '/' between birth and survival"))?;
+        let (first, second) = (first.trim(), second.trim());
+
+        let (birth, survival) = match (first.chars().next(), second.chars().next()) {
+            (Some('B' | 'b'), Some('S' | 's')) => (&first[1..], &second[1..]),
+            (Some('S' | 's'), Some('B' | 'b')) => (&second[1..], &first[1..]),
+            (Some('B' | 'b' | 'S' | 's'), _) | (_, Some('B' | 'b' | 'S' | 's')) => {
+                return Err(error("expected one B part and one S part"));
+            }
+            _ => (second, first),  // Legacy survival/birth digits
+        };
+
+        let parse_counts = |digits: &str| {
+            digits.chars().try_fold(0u16, |mask, digit| match digit.to_digit(10) {
+                Some(count) if count <= 8 => Ok(mask | 1 << count),
+                _ => Err(error("neighbour counts must be digits 0-8")),
+            })
+        };
+
+        Ok(Self { birth: parse_counts(birth)?, survival: parse_counts(survival)? })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u16| (0..=8).filter(|count| mask & (1 << count) != 0).map(|count| count.to_string()).collect::<String>();
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+pub struct NamedRule {
+    pub name : &'static str,
+    pub rule : Rule,
+}
+
+pub const RULES: &[NamedRule] = &[
+    NamedRule { name: "Conway's Life", rule: CONWAY },
+    NamedRule { name: "HighLife",      rule: Rule::new(&[3, 6], &[2, 3]) },
+    NamedRule { name: "Day & Night",   rule: Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]) },
+    NamedRule { name: "Seeds",         rule: Rule::new(&[2], &[]) },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(rulestring: &str) -> Result<Rule, &'static str> {
+        rulestring.parse::<Rule>().map_err(|error| error.reason)
+    }
+
+    #[test]
+    fn parses_birth_and_survival_in_either_order() {
+        assert_eq!(parse("B3/S23"), Ok(CONWAY));
+        assert_eq!(parse("S23/B3"), Ok(CONWAY));
+        assert_eq!(parse(" B36 / S23 "), Ok(Rule::new(&[3, 6], &[2, 3])));
+    }
+
+    #[test]
+    fn parses_lowercase() {
+        assert_eq!(parse("b3/s23"), Ok(CONWAY));
+        assert_eq!(parse("s23/b3"), Ok(CONWAY));
+    }
+
+    #[test]
+    fn parses_legacy_survival_birth_form() {
+        assert_eq!(parse("23/3"), Ok(CONWAY));
+        assert_eq!(parse("/2"), Ok(Rule::new(&[2], &[])));
+    }
+
+    #[test]
+    fn parses_empty_counts() {
+        assert_eq!(parse("B2/S"), Ok(Rule::new(&[2], &[])));
+        assert_eq!(parse("B/S"), Ok(Rule::new(&[], &[])));
+    }
+
+    #[test]
+    fn rejects_counts_above_eight() {
+        assert_eq!(parse("B9/S23"), Err("neighbour counts must be digits 0-8"));
+        assert_eq!(parse("B3/S29"), Err("neighbour counts must be digits 0-8"));
+        assert_eq!(parse("239/3"), Err("neighbour counts must be digits 0-8"));
+        assert_eq!(parse("B3x/S23"), Err("neighbour counts must be digits 0-8"));
+    }
+
+    #[test]
+    fn rejects_duplicate_or_missing_sections() {
+        assert_eq!(parse("B3/B23"), Err("expected one B part and one S part"));
+        assert_eq!(parse("S3/S23"), Err("expected one B part and one S part"));
+        assert_eq!(parse("B3/23"), Err("expected one B part and one S part"));
+        assert!(parse("B3/S2/S3").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert_eq!(parse(""), Err("expected a '/' between birth and survival"));
+        assert_eq!(parse("   "), Err("expected a '/' between birth and survival"));
+        assert_eq!(parse("B3S23"), Err("expected a '/' between birth and survival"));
+    }
+
+    #[test]
+    fn error_names_the_rulestring() {
+        let error = "B9/S23".parse::<Rule>().unwrap_err();
+        assert_eq!(error.to_string(), "invalid rule \"B9/S23\": neighbour counts must be digits 0-8");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for named in RULES {
+            let rulestring = named.rule.to_string();
+            assert_eq!(rulestring.parse::<Rule>(), Ok(named.rule), "{}", named.name);
+        }
+        assert_eq!(CONWAY.to_string(), "B3/S23");
+        assert_eq!(parse("S32/B3").unwrap().to_string(), "B3/S23");
+        assert_eq!(Rule::new(&[0, 8], &[]).to_string(), "B08/S");
+    }
+
+    #[test]
+    fn next_state_follows_the_masks() {
+        assert!(CONWAY.next_state(false, 3));
+        assert!(!CONWAY.next_state(false, 2));
+        assert!(CONWAY.next_state(true, 2));
+        assert!(!CONWAY.next_state(true, 4));
+    }
+}
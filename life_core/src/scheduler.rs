@@ -0,0 +1,280 @@
+// scheduler.rs - Which row coroutine gets the next time slice
+// The policy changes the order rows finish in and so how long a generation takes, never its result
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant};
+
+use crate::grid::TGrid;
+
+/// What a scheduler knows about one row coroutine at the start of a generation
+#[derive(Clone, Copy, Debug)]
+pub struct RowInfo {
+    pub row        : usize,      // Grid row computed by the coroutine
+    pub live_cells : usize,      // Live cells in the row and the rows either side, a cheap guess at activity
+    pub deadline   : Instant,    // When the row should be finished for the frame to fill in on time
+}
+
+impl RowInfo {
+    /// One entry per active row, deadlines are spread evenly from now to `deadline` top to bottom
+    pub fn for_grid(grid: &TGrid, deadline: Duration) -> Vec<RowInfo> {
+        let start = Instant::now();
+        let live_in_row = |row: usize| grid[row].iter().filter(|&&alive| alive).count();
+        grid.rows()
+            .map(|row| RowInfo {
+                row,
+                live_cells : live_in_row(row - 1) + live_in_row(row) + live_in_row(row + 1),
+                deadline   : start + deadline.mul_f64(row as f64 / grid.height() as f64),
+            })
+            .collect()
+    }
+}
+
+/// Picks the row coroutine to run next
+/// Each driver owns a scheduler for the rows it drives, one per band when the rows are split across threads
+pub trait Scheduler: Send {
+    /// Called before the first slice of each generation, `rows` lists the driver's coroutines in order
+    fn start_generation(&mut self, rows: &[RowInfo]);
+
+    /// Index of the next row to give a slice, `unfinished` has at least one true entry
+    fn next_row(&mut self, unfinished: &[bool]) -> usize;
+}
+
+/// The built-in scheduling policies
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    #[default]
+    RoundRobin,         // One slice per unfinished row in turn
+    Activity,           // Busiest rows first, each runs until it is done
+    EarliestDeadline,   // Row with the earliest deadline first
+    Random,             // Any unfinished row, uniformly
+}
+
+pub const SCHEDULING_POLICIES: &[SchedulingPolicy] = &[
+    SchedulingPolicy::RoundRobin,
+    SchedulingPolicy::Activity,
+    SchedulingPolicy::EarliestDeadline,
+    SchedulingPolicy::Random,
+];
+
+impl SchedulingPolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            SchedulingPolicy::RoundRobin       => "Round-robin",
+            SchedulingPolicy::Activity         => "Priority by activity",
+            SchedulingPolicy::EarliestDeadline => "Earliest deadline first",
+            SchedulingPolicy::Random           => "Random",
+        }
+    }
+
+    pub fn create_scheduler(self) -> Box<dyn Scheduler> {
+        match self {
+            SchedulingPolicy::RoundRobin       => Box::new(RoundRobin::default()),
+            SchedulingPolicy::Activity         => Box::new(Ordered::by(|row| std::cmp::Reverse(row.live_cells))),
+            SchedulingPolicy::EarliestDeadline => Box::new(Ordered::by(|row| row.deadline)),
+            SchedulingPolicy::Random           => Box::new(RandomPick::new(RandomState::new().hash_one(0u8))),
+        }
+    }
+}
+
+/// Cycles through the rows, skipping finished ones
+#[derive(Default)]
+pub struct RoundRobin {
+    cursor: usize,      // Row that gets the next slice if it is still unfinished
+}
+
+impl Scheduler for RoundRobin {
+    fn start_generation(&mut self, _rows: &[RowInfo]) {
+        self.cursor = 0;
+    }
+
+    fn next_row(&mut self, unfinished: &[bool]) -> usize {
+        let count = unfinished.len();
+        let row = (0..count)
+            .map(|offset| (self.cursor + offset) % count)
+            .find(|&i| unfinished[i])
+            .expect("no unfinished row to schedule");
+        self.cursor = (row + 1) % count;
+        row
+    }
+}
+
+/// Always runs the first unfinished row of a fixed order decided at the start of the generation
+/// Ties keep row order, so equally busy rows or equal deadlines fill in top to bottom
+pub struct Ordered<K: Ord> {
+    key      : fn(&RowInfo) -> K,
+    order    : Vec<usize>,
+    position : usize,    // Everything before this in `order` has finished
+}
+
+impl<K: Ord> Ordered<K> {
+    pub fn by(key: fn(&RowInfo) -> K) -> Self {
+        Self { key, order: Vec::new(), position: 0 }
+    }
+}
+
+impl<K: Ord> Scheduler for Ordered<K> {
+    fn start_generation(&mut self, rows: &[RowInfo]) {
+        self.order = (0..rows.len()).collect();
+        self.order.sort_by_key(|&i| (self.key)(&rows[i]));
+        self.position = 0;
+    }
+
+    fn next_row(&mut self, unfinished: &[bool]) -> usize {
+        // Rows never become unfinished again within a generation, so the position only moves forward
+        while !unfinished[self.order[self.position]] {
+            self.position += 1;
+        }
+        self.order[self.position]
+    }
+}
+
+/// Picks any unfinished row with a xorshift generator
+pub struct RandomPick {
+    state: u64,
+}
+
+impl RandomPick {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }  // Xorshift is stuck at zero
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+impl Scheduler for RandomPick {
+    fn start_generation(&mut self, _rows: &[RowInfo]) {}
+
+    fn next_row(&mut self, unfinished: &[bool]) -> usize {
+        let count = unfinished.iter().filter(|&&row| row).count();
+        let pick = (self.next_u64() % count as u64) as usize;
+        unfinished.iter()
+            .enumerate()
+            .filter(|&(_, &row)| row)
+            .nth(pick)
+            .map(|(i, _)| i)
+            .expect("no unfinished row to schedule")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows as (live cells, deadline in milliseconds from now)
+    fn rows(rows: &[(usize, u64)]) -> Vec<RowInfo> {
+        let start = Instant::now();
+        rows.iter()
+            .enumerate()
+            .map(|(i, &(live_cells, deadline))| RowInfo { row: i + 1, live_cells, deadline: start + Duration::from_millis(deadline) })
+            .collect()
+    }
+
+    /// The same rows with their deadlines in the opposite order
+    fn rows_reversed(rows: &[RowInfo]) -> Vec<RowInfo> {
+        let deadlines: Vec<Instant> = rows.iter().rev().map(|row| row.deadline).collect();
+        rows.iter().zip(deadlines).map(|(&row, deadline)| RowInfo { deadline, ..row }).collect()
+    }
+
+    /// Runs a generation where row `i` needs `slices[i]` slices, returning the order the slices went to
+    fn run(scheduler: &mut dyn Scheduler, rows: &[RowInfo], slices: &[usize]) -> Vec<usize> {
+        scheduler.start_generation(rows);
+        let mut remaining = slices.to_vec();
+        let mut order = Vec::new();
+        while remaining.iter().any(|&left| left > 0) {
+            let unfinished: Vec<bool> = remaining.iter().map(|&left| left > 0).collect();
+            let row = scheduler.next_row(&unfinished);
+            assert!(unfinished[row], "scheduled finished row {}", row);
+            remaining[row] -= 1;
+            order.push(row);
+        }
+        order
+    }
+
+    #[test]
+    fn round_robin_takes_turns_and_skips_finished_rows() {
+        let rows = rows(&[(0, 0); 3]);
+        let mut scheduler = RoundRobin::default();
+        assert_eq!(run(&mut scheduler, &rows, &[1, 3, 2]), vec![0, 1, 2, 1, 2, 1]);
+
+        // Every generation starts again from the first row
+        assert_eq!(run(&mut scheduler, &rows, &[2, 1, 1]), vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn activity_runs_the_busiest_rows_to_completion_first() {
+        let rows = rows(&[(2, 0), (9, 0), (5, 0), (9, 0)]);
+        let mut scheduler = Ordered::by(|row| std::cmp::Reverse(row.live_cells));
+        // Equally busy rows keep their top to bottom order
+        assert_eq!(run(&mut scheduler, &rows, &[1, 2, 1, 2]), vec![1, 1, 3, 3, 2, 0]);
+    }
+
+    #[test]
+    fn earliest_deadline_runs_the_most_urgent_row_first() {
+        let rows = rows(&[(0, 30), (0, 10), (0, 20), (0, 10)]);
+        let mut scheduler = Ordered::by(|row| row.deadline);
+        assert_eq!(run(&mut scheduler, &rows, &[1, 1, 2, 1]), vec![1, 3, 2, 2, 0]);
+
+        // The order is decided again each generation
+        let rows = rows_reversed(&rows);
+        assert_eq!(run(&mut scheduler, &rows, &[1, 1, 1, 1]), vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn random_is_reproducible_for_a_seed() {
+        let rows = rows(&[(0, 0); 8]);
+        let slices = [3, 1, 4, 1, 5, 9, 2, 6];
+        let first = run(&mut RandomPick::new(42), &rows, &slices);
+        assert_eq!(run(&mut RandomPick::new(42), &rows, &slices), first);
+        assert_ne!(run(&mut RandomPick::new(43), &rows, &slices), first);
+    }
+
+    #[test]
+    fn random_only_picks_unfinished_rows() {
+        let mut scheduler = RandomPick::new(7);
+        for _ in 0..100 {
+            assert_eq!(scheduler.next_row(&[false, false, true, false]), 2);
+        }
+
+        // Over many picks every unfinished row comes up
+        let mut picked = [false; 4];
+        for _ in 0..200 {
+            picked[scheduler.next_row(&[true, false, true, true])] = true;
+        }
+        assert_eq!(picked, [true, false, true, true]);
+    }
+
+    #[test]
+    fn random_survives_a_zero_seed() {
+        let mut scheduler = RandomPick::new(0);
+        let picks: Vec<usize> = (0..50).map(|_| scheduler.next_row(&[true; 5])).collect();
+        assert!(picks.iter().any(|&row| row != picks[0]));
+    }
+
+    #[test]
+    fn every_policy_gives_every_slice() {
+        let rows = rows(&[(4, 5), (1, 1), (7, 3)]);
+        for &policy in SCHEDULING_POLICIES {
+            let mut order = run(policy.create_scheduler().as_mut(), &rows, &[2, 3, 1]);
+            order.sort_unstable();
+            assert_eq!(order, vec![0, 0, 1, 1, 1, 2], "{}", policy.name());
+        }
+    }
+
+    #[test]
+    fn row_info_counts_the_rows_either_side() {
+        let mut grid = TGrid::new(5, 4);
+        grid[1][1] = true;
+        grid[2][3] = true;
+        grid[2][4] = true;
+        let rows = RowInfo::for_grid(&grid, Duration::from_millis(40));
+        let live: Vec<usize> = rows.iter().map(|row| row.live_cells).collect();
+        assert_eq!(live, vec![3, 3, 2, 0]);
+        assert!(rows.windows(2).all(|pair| pair[0].deadline < pair[1].deadline));
+    }
+}
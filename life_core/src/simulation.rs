@@ -0,0 +1,216 @@
+// simulation.rs - Runs a game on its own thread, decoupled from the UI frame loop
+// The UI sends commands and reads the latest published snapshot, it never waits for a generation
+// Edits cancel a generation in flight and are applied before the next one starts
+// Slow work like classifying runs on threads of its own and hands its result back as a command
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{CancelToken, GameOfLifeInterface};
+
+/// A game that a `SimulationThread` can drive
+/// `update_generation` is called whenever a generation is due while running, it may pause the game itself
+pub trait Simulation: GameOfLifeInterface + Send + 'static {
+    /// Immutable picture of everything the UI draws
+    type Snapshot: Send + Sync + 'static;
+
+    fn snapshot(&self) -> Self::Snapshot;
+    fn is_running(&self) -> bool;
+    fn set_running(&mut self, running: bool);
+    /// Time between generations while running
+    fn update_interval(&self) -> Duration;
+    /// Flips the cell at a display position
+    fn toggle_cell(&mut self, display_row: usize, display_col: usize);
+    /// Selects a catalogue pattern and applies it
+    fn load_pattern(&mut self, index: usize);
+
+    /// Token `update_generation` checks to abandon a generation part way, if it can
+    /// Edits sent to the thread cancel it, and it is reset once they have been applied
+    fn cancel_token(&self) -> Option<CancelToken> {
+        None
+    }
+
+    /// Hands the game a way to send itself commands, before it moves onto its thread
+    fn attach(&mut self, _handle: SimulationHandle<Self>) where Self: Sized {}
+}
+
+/// Messages from the UI, applied between generations in the order they were sent
+pub enum Command<S> {
+    Start,
+    Pause,
+    /// Advances one update whether running or not
+    Step,
+    ToggleCell { row: usize, col: usize },
+    LoadPattern(usize),
+    /// Any other change to the game
+    Apply(Box<dyn FnOnce(&mut S) + Send>),
+    /// A change that makes a generation computed from the old cells wrong, e.g. Clear
+    Edit(Box<dyn FnOnce(&mut S) + Send>),
+}
+
+impl<S> Command<S> {
+    /// Whether the generation in flight, if any, should be abandoned for this command
+    pub fn is_edit(&self) -> bool {
+        matches!(self, Command::ToggleCell { .. } | Command::LoadPattern(_) | Command::Edit(_))
+    }
+}
+
+/// UI-side handle to a game running on its own thread, dropping it stops the thread
+pub struct SimulationThread<S: Simulation> {
+    commands : Option<Arc<Sender<Command<S>>>>,     // Taken on drop so the thread sees the channel close
+    latest   : Arc<Mutex<Arc<S::Snapshot>>>,        // Swapped for a new snapshot after every change
+    thread   : Option<JoinHandle<()>>,
+    cancel   : Option<CancelToken>,                 // The game's token, tripped by every edit
+}
+
+impl<S: Simulation> SimulationThread<S> {
+    /// Moves `game` onto a new thread, `on_publish` runs after each new snapshot, e.g. to request a repaint
+    pub fn spawn(mut game: S, on_publish: impl Fn() + Send + 'static) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let commands = Arc::new(commands);
+        game.attach(SimulationHandle { commands: Arc::downgrade(&commands) });
+        let latest = Arc::new(Mutex::new(Arc::new(game.snapshot())));
+        let published = latest.clone();
+        let cancel = game.cancel_token();
+        let thread = thread::Builder::new()
+            .name("simulation".into())
+            .spawn(move || run(game, receiver, published, on_publish))
+            .expect("failed to start the simulation thread");
+
+        Self { commands: Some(commands), latest, thread: Some(thread), cancel }
+    }
+
+    pub fn send(&self, command: Command<S>) {
+        // Cancelled before queueing, so the thread's reset for this edit can only come after the cancel
+        // Cancelling first at worst abandons a generation that starts before the edit arrives, it is skipped and the next is due an interval later
+        if command.is_edit() && let Some(cancel) = &self.cancel {
+            cancel.cancel();
+        }
+
+        // Only fails if the thread has panicked, the game is gone and there is nothing left to tell
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+
+    /// Queues an arbitrary change to the game
+    pub fn apply(&self, change: impl FnOnce(&mut S) + Send + 'static) {
+        self.send(Command::Apply(Box::new(change)));
+    }
+
+    /// Queues a change to the cells, or anything else the next generation depends on, cancelling the one in flight
+    pub fn edit(&self, change: impl FnOnce(&mut S) + Send + 'static) {
+        self.send(Command::Edit(Box::new(change)));
+    }
+
+    /// The most recently published snapshot
+    pub fn snapshot(&self) -> Arc<S::Snapshot> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+impl<S: Simulation> Drop for SimulationThread<S> {
+    fn drop(&mut self) {
+        // Abandons a generation in flight, then the closed channel ends the thread
+        if let Some(cancel) = &self.cancel {
+            cancel.cancel();
+        }
+        self.commands.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Game-side handle for sending commands from other threads, it does not keep the simulation thread alive
+pub struct SimulationHandle<S> {
+    commands : Weak<Sender<Command<S>>>,
+}
+
+impl<S> Clone for SimulationHandle<S> {
+    fn clone(&self) -> Self {
+        Self { commands: self.commands.clone() }
+    }
+}
+
+impl<S: Send + 'static> SimulationHandle<S> {
+    /// Queues an arbitrary change to the game, dropped if the thread has stopped
+    pub fn apply(&self, change: impl FnOnce(&mut S) + Send + 'static) {
+        if let Some(commands) = self.commands.upgrade() {
+            let _ = commands.send(Command::Apply(Box::new(change)));
+        }
+    }
+
+    /// Runs `work` on a thread of its own and applies its result to the game, unless `cancel` is set first
+    /// The check is made on the simulation thread, so a result cancelled by an earlier command is never applied
+    pub fn spawn_work<T: Send + 'static>(
+        &self,
+        cancel : CancelToken,
+        work   : impl FnOnce(&CancelToken) -> Option<T> + Send + 'static,
+        apply  : impl FnOnce(&mut S, T) + Send + 'static,
+    ) {
+        let handle = self.clone();
+        thread::Builder::new()
+            .name("simulation work".into())
+            .spawn(move || {
+                let Some(result) = work(&cancel) else { return; };
+                handle.apply(move |game| {
+                    if !cancel.is_cancelled() {
+                        apply(game, result);
+                    }
+                });
+            })
+            .expect("failed to start a simulation work thread");
+    }
+}
+
+/// The simulation thread: sleeps until a command arrives or the next generation is due
+fn run<S: Simulation>(mut game: S, commands: Receiver<Command<S>>, latest: Arc<Mutex<Arc<S::Snapshot>>>, on_publish: impl Fn()) {
+    let publish = |game: &S| {
+        let snapshot = Arc::new(game.snapshot());
+        *latest.lock().unwrap() = snapshot;
+        on_publish();
+    };
+
+    let cancel = game.cancel_token();
+    let mut next_due = Instant::now();
+    loop {
+        let received = if game.is_running() {
+            commands.recv_timeout(next_due.saturating_duration_since(Instant::now()))
+        } else {
+            commands.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+
+        match received {
+            Ok(command) => {
+                let was_running = game.is_running();
+                // The edit's cancel landed before it was queued, so clearing it here never swallows one still to come
+                // A later edit that cancels meanwhile is queued right after, and its own reset follows
+                if command.is_edit() && let Some(cancel) = &cancel {
+                    cancel.reset();
+                }
+                match command {
+                    Command::Start => game.set_running(true),
+                    Command::Pause => game.set_running(false),
+                    Command::Step => game.update_generation(),
+                    Command::ToggleCell { row, col } => game.toggle_cell(row, col),
+                    Command::LoadPattern(index) => game.load_pattern(index),
+                    Command::Apply(change) | Command::Edit(change) => change(&mut game),
+                }
+                // A fresh start waits one interval before the first generation
+                if !was_running && game.is_running() {
+                    next_due = Instant::now() + game.update_interval();
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                game.update_generation();
+                next_due = Instant::now() + game.update_interval();
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        publish(&game);
+    }
+}
@@ -0,0 +1,247 @@
+// sparse_engine.rs - Unbounded universe backed by a set of live cells
+// Only live cells and their neighbours are visited, so patterns are never clipped
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::cycle::{Cycle, CycleDetector};
+use crate::patterns::PATTERNS;
+use crate::rules::Rule;
+use crate::topology::Boundary;
+use crate::universe::{BoundingBox, Universe};
+use crate::GameOfLifeInterface;
+
+/// (row, col) of a cell in the unbounded universe
+pub type Cell = (i64, i64);
+
+/// Sparse Game of Life over signed 64-bit coordinates
+/// Rules that give birth on 0 neighbours would fill the whole plane, so births are only
+/// considered next to live cells
+pub struct SparseLife {
+    live                 : HashSet<Cell>,
+    neighbor_counts      : HashMap<Cell, u8>,    // Scratch map reused between generations
+    rule                 : Rule,
+    generation           : u64,
+    pub selected_pattern : usize,
+    cycle_detector       : CycleDetector<HashSet<Cell>>,
+}
+
+impl SparseLife {
+    pub fn new(rule: Rule) -> Self {
+        Self {
+            live             : HashSet::new(),
+            neighbor_counts  : HashMap::new(),
+            rule,
+            generation       : 0,
+            selected_pattern : 0,
+            cycle_detector   : CycleDetector::default(),
+        }
+    }
+
+    pub fn live_cells(&self) -> impl Iterator<Item = &Cell> {
+        self.live.iter()
+    }
+}
+
+impl GameOfLifeInterface for SparseLife {
+    fn update_generation(&mut self) {
+        // Every live cell adds one to each of its eight neighbours
+        self.neighbor_counts.clear();
+        for &(row, col) in &self.live {
+            for dr in -1..=1 {
+                for dc in -1..=1 {
+                    if (dr, dc) != (0, 0) {
+                        *self.neighbor_counts.entry((row + dr, col + dc)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        // Survivors are live cells the rule keeps, births come from the counted neighbours
+        let rule = self.rule;
+        let mut next = HashSet::with_capacity(self.live.len());
+        for (&cell, &count) in &self.neighbor_counts {
+            if rule.next_state(self.live.contains(&cell), count) {
+                next.insert(cell);
+            }
+        }
+        for &cell in &self.live {
+            if !self.neighbor_counts.contains_key(&cell) && rule.next_state(true, 0) {
+                next.insert(cell);
+            }
+        }
+
+        self.live = next;
+        self.generation += 1;
+    }
+
+    fn hash_grid(&self) -> u64 {
+        let mut cells: Vec<&Cell> = self.live.iter().collect();
+        cells.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        cells.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn check_for_cycle(&mut self) -> Option<Cycle> {
+        let current_hash = self.hash_grid();
+        self.cycle_detector.observe(self.generation, current_hash, self.live.len() as u64, &self.live)
+    }
+
+    fn clear_grid(&mut self) {
+        self.live.clear();
+        self.generation = 0;
+        self.cycle_detector.reset();
+    }
+
+    fn apply_selected_pattern(&mut self) {
+        if let Some(pattern) = PATTERNS.get(self.selected_pattern) {
+            self.apply_pattern(pattern);
+        }
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.cycle_detector.reset();
+    }
+
+    /// The universe has no edges, so every boundary behaves the same
+    fn set_boundary(&mut self, _boundary: Boundary) {}
+
+    /// There is no border to go live
+    fn check_border_cells_dead(&self) -> bool {
+        true
+    }
+}
+
+impl Universe for SparseLife {
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn population(&self) -> u64 {
+        self.live.len() as u64
+    }
+
+    fn is_alive(&self, row: i64, col: i64) -> bool {
+        self.live.contains(&(row, col))
+    }
+
+    fn set_cell(&mut self, row: i64, col: i64, alive: bool) {
+        self.cycle_detector.reset();
+        if alive {
+            self.live.insert((row, col));
+        } else {
+            self.live.remove(&(row, col));
+        }
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut cells = self.live.iter();
+        let &(row, col) = cells.next()?;
+        let mut bounds = BoundingBox { top: row, left: col, bottom: row, right: col };
+        for &(row, col) in cells {
+            bounds.include(row, col);
+        }
+        Some(bounds)
+    }
+
+    fn collect_live_cells(&self) -> Vec<Cell> {
+        self.live.iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::closure_engine::ClosureEngine;
+    use crate::grid::TGrid;
+    use crate::patterns;
+    use crate::GridEngine;
+
+    #[test]
+    fn matches_the_closure_engine_away_from_the_border() {
+        // A random soup in the middle of a grid wide enough that nothing reaches its border in time
+        let mut soup = TGrid::new(20, 20);
+        patterns::apply_random_pattern(&mut soup, 5);
+        let mut grid = TGrid::new(60, 60);
+        for row in soup.rows() {
+            for col in soup.cols() {
+                grid[row + 20][col + 20] = soup[row][col];
+            }
+        }
+
+        let mut sparse = SparseLife::new(Rule::default());
+        sparse.load_grid(&grid, 1, 1);
+        let mut engine = ClosureEngine::new(60, 60, Rule::default(), Boundary::DeadBorder);
+        let mut from_sparse = TGrid::new(60, 60);
+        for generation in 1..=15 {
+            engine.step(&mut grid);
+            sparse.update_generation();
+            sparse.copy_to_grid(&mut from_sparse, 1, 1);
+            assert_eq!(from_sparse, grid, "generation {}", generation);
+            assert_eq!(sparse.population(), grid.count_live_cells() as u64);
+        }
+    }
+
+    #[test]
+    fn matches_the_closure_engine_for_other_rules() {
+        let highlife = Rule::new(&[3, 6], &[2, 3]);
+        let mut grid = TGrid::new(40, 40);
+        patterns::apply_random_pattern(&mut grid, 9);
+        for row in grid.rows() {
+            for col in grid.cols() {
+                if !(15..25).contains(&row) || !(15..25).contains(&col) { grid[row][col] = false; }
+            }
+        }
+
+        let mut sparse = SparseLife::new(highlife);
+        sparse.load_grid(&grid, 1, 1);
+        let mut engine = ClosureEngine::new(40, 40, highlife, Boundary::DeadBorder);
+        let mut from_sparse = TGrid::new(40, 40);
+        for generation in 1..=8 {
+            engine.step(&mut grid);
+            sparse.update_generation();
+            sparse.copy_to_grid(&mut from_sparse, 1, 1);
+            assert_eq!(from_sparse, grid, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn grows_past_the_original_bounds() {
+        // A glider heading up and to the left crosses into negative coordinates
+        let mut sparse = SparseLife::new(Rule::default());
+        for (row, col) in [(0, 0), (0, 1), (0, 2), (1, 0), (2, 1)] {
+            sparse.set_cell(row, col, true);
+        }
+        let start = sparse.bounding_box().unwrap();
+
+        for _ in 0..400 {
+            sparse.update_generation();
+        }
+        let moved = sparse.bounding_box().unwrap();
+        assert_eq!(sparse.population(), 5);
+        assert_eq!((moved.width(), moved.height()), (start.width(), start.height()));
+        assert_eq!((moved.top - start.top, moved.left - start.left), (-100, -100));
+        assert_eq!(sparse.generation(), 400);
+    }
+
+    #[test]
+    fn r_pentomino_grows_beyond_a_50_by_50_grid() {
+        let mut sparse = SparseLife::new(Rule::default());
+        for (row, col) in [(0, 1), (0, 2), (1, 0), (1, 1), (2, 1)] {
+            sparse.set_cell(row, col, true);
+        }
+
+        for _ in 0..1103 {
+            sparse.update_generation();
+        }
+        let bounds = sparse.bounding_box().unwrap();
+        assert!(bounds.width() > 50 && bounds.height() > 50, "{:?}", bounds);
+        assert!(bounds.top < 0 && bounds.left < 0);
+        // Its final population once settled, escaped gliders included
+        assert_eq!(sparse.population(), 116);
+    }
+}
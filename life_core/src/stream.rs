@@ -0,0 +1,284 @@
+// stream.rs - Generations handed out one at a time, as an async stream or a plain iterator
+// Nothing is computed ahead, the next generation starts only when the consumer asks for it
+// Both can end early once the grid repeats, so `take`, `skip` and friends work on finite runs too
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::coroutine_engine::GenerationProcessor;
+use crate::cycle::{self, Cycle, CycleDetector};
+use crate::grid::TGrid;
+use crate::GridEngine;
+
+/// One generation as the streams hand it out
+#[derive(Clone, Debug)]
+pub struct GridSnapshot {
+    pub generation : u64,          // 1 for the first step from the starting grid
+    pub grid       : Arc<TGrid>,
+    pub cycle      : Option<Cycle>, // Set once the generations are known to repeat
+}
+
+/// Generation count and cycle detection shared by the stream and the iterator
+struct Tracker {
+    generation    : u64,
+    cycles        : CycleDetector<Arc<TGrid>>,
+    stop_on_cycle : bool,
+    finished      : bool,          // Set after yielding the generation that closed a cycle when stopping on cycles
+}
+
+impl Tracker {
+    fn new(start: &Arc<TGrid>) -> Self {
+        let mut cycles = CycleDetector::default();
+        cycles.observe(0, cycle::hash_grid(start), start.count_live_cells() as u64, start);
+        Self { generation: 0, cycles, stop_on_cycle: false, finished: false }
+    }
+
+    fn advance(&mut self, grid: Arc<TGrid>) -> GridSnapshot {
+        self.generation += 1;
+        let cycle = self.cycles.observe(self.generation, cycle::hash_grid(&grid), grid.count_live_cells() as u64, &grid);
+        self.finished = cycle.is_some() && self.stop_on_cycle;
+        GridSnapshot { generation: self.generation, grid, cycle }
+    }
+}
+
+/// The processor travels into the future computing a generation and comes back with the result
+type Computing = Pin<Box<dyn Future<Output = (GenerationProcessor, TGrid)> + Send>>;
+
+enum StreamState {
+    Idle(GenerationProcessor, Arc<TGrid>),   // Holding the last grid until the next poll
+    Computing(Computing),
+    Done,                                    // Only while switching between the two
+}
+
+/// Generations computed by the time-sliced row coroutines, each one only once it is polled for
+/// A slow consumer simply leaves the processor idle, there is no buffer to fill up
+/// Polled from inside a tokio runtime when the processor uses more than one thread
+/// Dropping it abandons the generation in flight, though row bands already on other threads run to the end of it
+pub struct GenerationStream {
+    state   : StreamState,
+    tracker : Tracker,
+}
+
+impl GenerationStream {
+    /// Streams the generations after `start`, which must match the processor's size
+    pub fn new(processor: GenerationProcessor, start: TGrid) -> Self {
+        let start = Arc::new(start);
+        let tracker = Tracker::new(&start);
+        Self { state: StreamState::Idle(processor, start), tracker }
+    }
+
+    /// Ends the stream after the generation that closes a cycle
+    pub fn stop_on_cycle(mut self) -> Self {
+        self.tracker.stop_on_cycle = true;
+        self
+    }
+
+    /// The next generation, `None` once the stream has stopped on a cycle
+    pub async fn next(&mut self) -> Option<GridSnapshot> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Gives the processor back, unless a generation is still being computed
+    pub fn into_processor(self) -> Option<GenerationProcessor> {
+        match self.state {
+            StreamState::Idle(processor, _) => Some(processor),
+            _ => None,
+        }
+    }
+}
+
+impl Stream for GenerationStream {
+    type Item = GridSnapshot;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<GridSnapshot>> {
+        let this = self.get_mut();
+        if this.tracker.finished {
+            return Poll::Ready(None);
+        }
+
+        this.state = match std::mem::replace(&mut this.state, StreamState::Done) {
+            StreamState::Idle(mut processor, grid) => StreamState::Computing(Box::pin(async move {
+                let next_grid = processor.process_generation(grid).await;
+                (processor, next_grid)
+            })),
+            state => state,
+        };
+
+        let StreamState::Computing(computing) = &mut this.state else { return Poll::Ready(None) };
+        let (processor, next_grid) = std::task::ready!(computing.as_mut().poll(cx));
+        let next_grid = Arc::new(next_grid);
+        this.state = StreamState::Idle(processor, next_grid.clone());
+        Poll::Ready(Some(this.tracker.advance(next_grid)))
+    }
+}
+
+/// Generations from any bounded engine, computed on the calling thread as the iterator is advanced
+pub struct Generations {
+    engine  : Box<dyn GridEngine>,
+    grid    : TGrid,
+    tracker : Tracker,
+}
+
+impl Generations {
+    /// Iterates over the generations after `start`, which must match the engine's size
+    pub fn new(engine: Box<dyn GridEngine>, start: TGrid) -> Self {
+        let tracker = Tracker::new(&Arc::new(start.clone()));
+        Self { engine, grid: start, tracker }
+    }
+
+    /// Ends the iteration after the generation that closes a cycle
+    pub fn stop_on_cycle(mut self) -> Self {
+        self.tracker.stop_on_cycle = true;
+        self
+    }
+}
+
+impl Iterator for Generations {
+    type Item = GridSnapshot;
+
+    fn next(&mut self) -> Option<GridSnapshot> {
+        if self.tracker.finished {
+            return None;
+        }
+        self.engine.step(&mut self.grid);
+        Some(self.tracker.advance(Arc::new(self.grid.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+    use std::time::Duration;
+
+    use crate::closure_engine::ClosureEngine;
+    use crate::grid::GRID_START;
+    use crate::test_support::{closure_generations, random_grid};
+    use crate::rules::Rule;
+    use crate::topology::Boundary;
+
+    const WIDTH: usize = 24;
+    const HEIGHT: usize = 18;
+
+    fn soup() -> TGrid {
+        random_grid(WIDTH, HEIGHT, 31)
+    }
+
+    /// A processor whose slices are long enough to finish every row they start
+    fn processor(width: usize, height: usize, boundary: Boundary) -> GenerationProcessor {
+        GenerationProcessor::new(width, height, Rule::default(), boundary, Duration::from_secs(1))
+    }
+
+    /// A horizontal blinker in the middle of a 5x5 torus, period 2 from the start
+    fn blinker() -> TGrid {
+        let mut grid = TGrid::new(5, 5);
+        for col in 2..=4 {
+            grid[3][col] = true;
+        }
+        grid
+    }
+
+    #[tokio::test]
+    async fn stream_yields_the_stepped_generations() {
+        let expected = closure_generations(&soup(), Rule::default(), Boundary::Torus, 20);
+        let mut stream = GenerationStream::new(processor(WIDTH, HEIGHT, Boundary::Torus), soup());
+        for (i, expected) in expected.iter().enumerate() {
+            let snapshot = stream.next().await.expect("stream ended early");
+            assert_eq!(snapshot.generation, i as u64 + 1);
+            assert_eq!(*snapshot.grid, *expected, "generation {}", i + 1);
+        }
+        assert!(stream.into_processor().is_some());
+    }
+
+    #[test]
+    fn iterator_yields_the_stepped_generations() {
+        let expected = closure_generations(&soup(), Rule::default(), Boundary::KleinBottle, 20);
+        let engine = Box::new(ClosureEngine::new(WIDTH, HEIGHT, Rule::default(), Boundary::KleinBottle));
+        let generations: Vec<GridSnapshot> = Generations::new(engine, soup()).take(20).collect();
+        assert_eq!(generations.iter().map(|snapshot| snapshot.generation).collect::<Vec<_>>(), (1..=20).collect::<Vec<_>>());
+        for (snapshot, expected) in generations.iter().zip(&expected) {
+            assert_eq!(*snapshot.grid, *expected, "generation {}", snapshot.generation);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn stream_and_iterator_agree_on_several_threads() {
+        let mut processor = processor(WIDTH, HEIGHT, Boundary::DeadBorder);
+        processor.set_threads(3);
+        let mut stream = GenerationStream::new(processor, soup());
+        let engine = Box::new(ClosureEngine::new(WIDTH, HEIGHT, Rule::default(), Boundary::DeadBorder));
+        for from_iterator in Generations::new(engine, soup()).take(10) {
+            let from_stream = stream.next().await.expect("stream ended early");
+            assert_eq!(from_stream.generation, from_iterator.generation);
+            assert_eq!(from_stream.grid, from_iterator.grid);
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_stops_after_closing_a_cycle() {
+        let mut stream = GenerationStream::new(processor(5, 5, Boundary::Torus), blinker()).stop_on_cycle();
+        // Brent's algorithm only moves its checkpoint to generation 1 after the first step, so it meets it again at 3
+        for _ in 0..2 {
+            assert_eq!(stream.next().await.unwrap().cycle, None);
+        }
+        let third = stream.next().await.unwrap();
+        assert_eq!(third.cycle.map(|cycle| (cycle.period, cycle.start, cycle.detected_at)), Some((2, 0, 3)));
+        assert!(stream.next().await.is_none());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn iterator_stops_after_closing_a_cycle() {
+        let engine = Box::new(ClosureEngine::new(5, 5, Rule::default(), Boundary::Torus));
+        let generations: Vec<GridSnapshot> = Generations::new(engine, blinker()).stop_on_cycle().collect();
+        assert_eq!(generations.len(), 3);
+        assert_eq!(generations[2].cycle.map(|cycle| cycle.period), Some(2));
+
+        // Without stopping, the cycle is reported on every generation after it closes
+        let engine = Box::new(ClosureEngine::new(5, 5, Rule::default(), Boundary::Torus));
+        let cycles: Vec<bool> = Generations::new(engine, blinker()).take(5).map(|snapshot| snapshot.cycle.is_some()).collect();
+        assert_eq!(cycles, vec![false, false, true, true, true]);
+    }
+
+    #[test]
+    fn dropping_the_stream_abandons_the_generation_in_flight() {
+        let mut processor = GenerationProcessor::new(WIDTH, HEIGHT, Rule::default(), Boundary::Torus, Duration::ZERO);
+        processor.set_progressive(true);
+        let progress = processor.progress().unwrap();
+
+        // With no budget the first slice yields before its row is done, leaving the generation part done
+        let mut stream = GenerationStream::new(processor, soup());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+        let started = progress.snapshot();
+        assert!(started.in_progress);
+        assert!(stream.into_processor().is_none(), "a generation is still being computed");
+
+        // Nothing is left running to carry on with it
+        std::thread::sleep(Duration::from_millis(20));
+        let later = progress.snapshot();
+        assert_eq!(later.cursors, started.cursors);
+        assert!((GRID_START..=HEIGHT).any(|row| !later.row_finished(row)));
+    }
+
+    #[tokio::test]
+    async fn nothing_is_computed_ahead_of_the_consumer() {
+        use tokio_stream::StreamExt;
+
+        let mut processor = processor(WIDTH, HEIGHT, Boundary::Torus);
+        processor.set_progressive(true);
+        let progress = processor.progress().unwrap();
+
+        let grids: Vec<Arc<TGrid>> = GenerationStream::new(processor, soup()).take(3).map(|snapshot| snapshot.grid).collect().await;
+        assert_eq!(grids.len(), 3);
+
+        // The third generation is the last one begun, and it finished
+        let last = progress.snapshot();
+        assert!(!last.in_progress);
+        assert_eq!(last.next_grid, *grids[2]);
+    }
+}
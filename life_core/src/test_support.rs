@@ -0,0 +1,43 @@
+// test_support.rs - Fixtures shared by the unit tests
+// Grids built cell by cell or from a random soup, and the closure engine as the reference the other engines are checked against
+
+use crate::closure_engine::ClosureEngine;
+use crate::grid::TGrid;
+use crate::patterns;
+use crate::rules::Rule;
+use crate::topology::Boundary;
+use crate::GridEngine;
+
+/// A grid with only `cells` alive
+pub fn grid_with(width: usize, height: usize, cells: &[(usize, usize)]) -> TGrid {
+    let mut grid = TGrid::new(width, height);
+    for &(row, col) in cells {
+        grid[row][col] = true;
+    }
+    grid
+}
+
+/// The random soup `seed` gives on an empty grid
+pub fn random_grid(width: usize, height: usize, seed: u32) -> TGrid {
+    let mut grid = TGrid::new(width, height);
+    patterns::apply_random_pattern(&mut grid, seed);
+    grid
+}
+
+/// The first `count` generations after `start` from stepping the closure engine directly
+pub fn closure_generations(start: &TGrid, rule: Rule, boundary: Boundary, count: usize) -> Vec<TGrid> {
+    let mut engine = ClosureEngine::new(start.width(), start.height(), rule, boundary);
+    let mut grid = start.clone();
+    (0..count).map(|_| { engine.step(&mut grid); grid.clone() }).collect()
+}
+
+/// Steps a random soup with `engine` and checks every generation against the closure engine
+/// `engine` has to be set up for the same size, rule and boundary already
+pub fn assert_matches_closure_engine(engine: &mut dyn GridEngine, width: usize, height: usize, rule: Rule, boundary: Boundary, seed: u32, generations: usize) {
+    let start = random_grid(width, height, seed);
+    let mut grid = start.clone();
+    for (generation, expected) in closure_generations(&start, rule, boundary, generations).iter().enumerate() {
+        engine.step(&mut grid);
+        assert_eq!(&grid, expected, "{}x{} {:?} {} generation {}", width, height, boundary, rule, generation + 1);
+    }
+}
@@ -0,0 +1,155 @@
+// topology.rs - What lies beyond the edges of the active area
+
+/// How neighbours are found for cells on the edge of the active area
+/// Coordinates use the bordered layout, the active area is `1..=height` by `1..=width`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Boundary {
+    /// Permanently dead cells all round, patterns die at the wall
+    #[default]
+    DeadBorder,
+    /// Left/right and top/bottom edges joined, gliders re-enter on the opposite side
+    Torus,
+    /// Top/bottom joined as a torus, left/right joined with a vertical flip
+    KleinBottle,
+    /// Both pairs of edges joined with a flip (real projective plane)
+    CrossSurface,
+}
+
+pub const BOUNDARIES: &[Boundary] = &[
+    Boundary::DeadBorder,
+    Boundary::Torus,
+    Boundary::KleinBottle,
+    Boundary::CrossSurface,
+];
+
+impl Boundary {
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::DeadBorder   => "Dead border",
+            Boundary::Torus        => "Torus",
+            Boundary::KleinBottle  => "Klein bottle",
+            Boundary::CrossSurface => "Cross-surface",
+        }
+    }
+
+    /// Maps a neighbour position, which may sit on the border, to the cell it stands for
+    /// With a dead border the position is returned unchanged and so reads as dead
+    pub fn wrap(self, row: usize, col: usize, width: usize, height: usize) -> (usize, usize) {
+        let (flip_row_at_sides, flip_col_at_ends) = match self {
+            Boundary::DeadBorder   => return (row, col),
+            Boundary::Torus        => (false, false),
+            Boundary::KleinBottle  => (true, false),
+            Boundary::CrossSurface => (true, true),
+        };
+        let (mut row, mut col) = (row, col);
+
+        // Crossing the left or right edge
+        if col == 0 || col == width + 1 {
+            col = if col == 0 { width } else { 1 };
+            if flip_row_at_sides { row = height + 1 - row; }
+        }
+
+        // Crossing the top or bottom edge
+        if row == 0 || row == height + 1 {
+            row = if row == 0 { height } else { 1 };
+            if flip_col_at_ends { col = width + 1 - col; }
+        }
+
+        (row, col)
+    }
+
+    /// The eight neighbour positions of an active cell, already wrapped
+    pub fn neighbors(self, row: usize, col: usize, width: usize, height: usize) -> [(usize, usize); 8] {
+        [
+            (row-1,col-1),(row-1,col),(row-1,col+1),
+            (row,col-1),              (row,col+1),
+            (row+1,col-1),(row+1,col),(row+1,col+1)
+        ].map(|(nr, nc)| self.wrap(nr, nc, width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4 wide by 3 high active area, rows 1..=3 and columns 1..=4
+    const WIDTH: usize = 4;
+    const HEIGHT: usize = 3;
+
+    fn neighbors(boundary: Boundary, row: usize, col: usize) -> [(usize, usize); 8] {
+        boundary.neighbors(row, col, WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn dead_border_leaves_neighbours_on_the_border() {
+        assert_eq!(neighbors(Boundary::DeadBorder, 1, 1), [(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(neighbors(Boundary::DeadBorder, 3, 4), [(2, 3), (2, 4), (2, 5), (3, 3), (3, 5), (4, 3), (4, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn torus_corner_neighbours() {
+        assert_eq!(neighbors(Boundary::Torus, 1, 1), [(3, 4), (3, 1), (3, 2), (1, 4), (1, 2), (2, 4), (2, 1), (2, 2)]);
+        assert_eq!(neighbors(Boundary::Torus, 3, 4), [(2, 3), (2, 4), (2, 1), (3, 3), (3, 1), (1, 3), (1, 4), (1, 1)]);
+    }
+
+    #[test]
+    fn torus_edge_neighbours() {
+        assert_eq!(neighbors(Boundary::Torus, 2, 4), [(1, 3), (1, 4), (1, 1), (2, 3), (2, 1), (3, 3), (3, 4), (3, 1)]);
+        assert_eq!(neighbors(Boundary::Torus, 1, 2), [(3, 1), (3, 2), (3, 3), (1, 1), (1, 3), (2, 1), (2, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn klein_bottle_reflects_rows_across_the_sides() {
+        // Leaving through the right edge comes back in on the left, upside down
+        assert_eq!(neighbors(Boundary::KleinBottle, 2, 4), [(1, 3), (1, 4), (3, 1), (2, 3), (2, 1), (3, 3), (3, 4), (1, 1)]);
+        // The top and bottom are joined straight, as on the torus
+        assert_eq!(neighbors(Boundary::KleinBottle, 1, 2), [(3, 1), (3, 2), (3, 3), (1, 1), (1, 3), (2, 1), (2, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn klein_bottle_corner_neighbours() {
+        assert_eq!(neighbors(Boundary::KleinBottle, 1, 1), [(1, 4), (3, 1), (3, 2), (3, 4), (1, 2), (2, 4), (2, 1), (2, 2)]);
+        assert_eq!(neighbors(Boundary::KleinBottle, 3, 4), [(2, 3), (2, 4), (2, 1), (3, 3), (1, 1), (1, 3), (1, 4), (3, 1)]);
+    }
+
+    #[test]
+    fn cross_surface_reflects_both_pairs_of_edges() {
+        assert_eq!(neighbors(Boundary::CrossSurface, 2, 4), [(1, 3), (1, 4), (3, 1), (2, 3), (2, 1), (3, 3), (3, 4), (1, 1)]);
+        assert_eq!(neighbors(Boundary::CrossSurface, 1, 2), [(3, 4), (3, 3), (3, 2), (1, 1), (1, 3), (2, 1), (2, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn cross_surface_corner_neighbours() {
+        // The corners are where the projective plane is pinched, so a corner cell is its own diagonal
+        // neighbour and two of its wrapped neighbours land on the same cell
+        assert_eq!(neighbors(Boundary::CrossSurface, 1, 1), [(1, 1), (3, 4), (3, 3), (3, 4), (1, 2), (2, 4), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn wrapping_boundaries_only_name_active_cells() {
+        for boundary in [Boundary::Torus, Boundary::KleinBottle, Boundary::CrossSurface] {
+            for row in 1..=HEIGHT {
+                for col in 1..=WIDTH {
+                    for (nr, nc) in neighbors(boundary, row, col) {
+                        assert!((1..=HEIGHT).contains(&nr) && (1..=WIDTH).contains(&nc), "{:?} ({}, {}) -> ({}, {})", boundary, row, col, nr, nc);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn torus_and_klein_bottle_neighbours_are_mutual() {
+        for boundary in [Boundary::Torus, Boundary::KleinBottle] {
+            for row in 1..=HEIGHT {
+                for col in 1..=WIDTH {
+                    for (nr, nc) in neighbors(boundary, row, col) {
+                        let there = neighbors(boundary, nr, nc).iter().filter(|&&cell| cell == (row, col)).count();
+                        let here = neighbors(boundary, row, col).iter().filter(|&&cell| cell == (nr, nc)).count();
+                        assert_eq!(here, there, "{:?} ({}, {}) and ({}, {})", boundary, row, col, nr, nc);
+                    }
+                }
+            }
+        }
+    }
+}
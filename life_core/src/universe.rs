@@ -0,0 +1,105 @@
+// universe.rs - Unbounded universes addressed with signed 64-bit coordinates
+
+use std::fmt;
+
+use crate::grid::{TGrid, GRID_START};
+use crate::patterns::Pattern;
+use crate::GameOfLifeInterface;
+
+/// Inclusive bounds of the live population
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BoundingBox {
+    pub top    : i64,
+    pub left   : i64,
+    pub bottom : i64,
+    pub right  : i64,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> u64 { self.left.abs_diff(self.right) + 1 }
+    pub fn height(&self) -> u64 { self.top.abs_diff(self.bottom) + 1 }
+
+    /// Centre cell as (row, col), rounded towards the top-left
+    pub fn center(&self) -> (i64, i64) {
+        (self.top + (self.bottom - self.top) / 2, self.left + (self.right - self.left) / 2)
+    }
+
+    /// Grows the box to include (row, col)
+    pub fn include(&mut self, row: i64, col: i64) {
+        self.top = self.top.min(row);
+        self.bottom = self.bottom.max(row);
+        self.left = self.left.min(col);
+        self.right = self.right.max(col);
+    }
+}
+
+/// Why a universe stopped advancing, it stays as it was until cleared
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UniverseLimit {
+    Outgrown,                      // The pattern reached the edge of the largest universe the engine can address
+    GenerationOverflow,            // The next update would take the generation count past u64::MAX
+}
+
+impl fmt::Display for UniverseLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniverseLimit::Outgrown => write!(f, "pattern has outgrown the 64-bit universe"),
+            UniverseLimit::GenerationOverflow => write!(f, "generation count would overflow"),
+        }
+    }
+}
+
+/// An engine whose universe has no edges, drawn through a movable viewport
+/// `update_generation` only advances the universe, callers run `check_for_cycle` themselves
+/// Pattern and grid cell (row, col) sit at universe (row, col), so the default viewport origin is (1, 1)
+pub trait Universe: GameOfLifeInterface {
+    fn generation(&self) -> u64;
+    fn population(&self) -> u64;
+    fn is_alive(&self, row: i64, col: i64) -> bool;
+    fn set_cell(&mut self, row: i64, col: i64, alive: bool);
+    fn bounding_box(&self) -> Option<BoundingBox>;
+    /// Every live cell as (row, col), in no particular order, without probing the dead space between them
+    fn collect_live_cells(&self) -> Vec<(i64, i64)>;
+
+    /// Each `update_generation` advances 2^step_log2 generations, engines that only single-step stay at 0
+    fn step_log2(&self) -> u8 { 0 }
+    fn set_step_log2(&mut self, _step_log2: u8) {}
+
+    /// Set once `update_generation` can no longer advance, it then leaves the universe alone until it is cleared
+    fn limit(&self) -> Option<UniverseLimit> { None }
+
+    fn toggle_cell(&mut self, row: i64, col: i64) {
+        let alive = self.is_alive(row, col);
+        self.set_cell(row, col, !alive);
+    }
+
+    /// Clears the universe and places `pattern` at its catalogue coordinates, unclipped
+    fn apply_pattern(&mut self, pattern: &Pattern) {
+        self.clear_grid();
+        for &(row, col) in pattern.cells {
+            self.set_cell(row as i64, col as i64, true);
+        }
+    }
+
+    /// Clears the universe and copies the active area of `grid` in with grid[1][1] at (top, left)
+    fn load_grid(&mut self, grid: &TGrid, top: i64, left: i64) {
+        self.clear_grid();
+        for row in grid.rows() {
+            for col in grid.cols() {
+                if grid[row][col] {
+                    self.set_cell(top + (row - GRID_START) as i64, left + (col - GRID_START) as i64, true);
+                }
+            }
+        }
+    }
+
+    /// Copies the window with its top-left corner at (top, left) into the active area of `grid`
+    fn copy_to_grid(&self, grid: &mut TGrid, top: i64, left: i64) {
+        grid.clear();
+        for row in grid.rows() {
+            for col in grid.cols() {
+                grid[row][col] = self.is_alive(top + (row - GRID_START) as i64, left + (col - GRID_START) as i64);
+            }
+        }
+    }
+}
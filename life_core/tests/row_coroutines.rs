@@ -0,0 +1,201 @@
+// row_coroutines.rs - Row coroutines resume where they stopped, always make progress and can share a scheduler
+// Regression tests for slices that threw their progress away, which never finished with a budget under one row
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use life_core::closure_engine::ClosureEngine;
+use life_core::coroutine_engine::{create_time_sliced_row_coroutine, GenerationProcessor, SharedScheduler};
+use life_core::{patterns, Boundary, CancelToken, GridEngine, Rule, TGrid, GRID_START};
+
+/// Longest any of these generations may take before the test counts it as stuck
+/// A watchdog cancels a stuck generation, which also stops row bands spinning on blocking threads
+/// Tests that run a generation use several workers, so the watchdog still gets to run while it spins
+const STUCK: Duration = Duration::from_secs(20);
+
+fn random_grid(width: usize, height: usize, seed: u32) -> TGrid {
+    let mut grid = TGrid::new(width, height);
+    patterns::apply_random_pattern(&mut grid, seed);
+    grid
+}
+
+/// The next generation as the closure engine computes it
+fn expected(grid: &TGrid, boundary: Boundary) -> TGrid {
+    let mut next = grid.clone();
+    ClosureEngine::new(grid.width(), grid.height(), Rule::default(), boundary).step(&mut next);
+    next
+}
+
+async fn run_generations(processor: &mut GenerationProcessor, mut grid: TGrid, boundary: Boundary, generations: usize) {
+    for _ in 0..generations {
+        let cancel = CancelToken::default();
+        let watchdog = tokio::spawn({
+            let cancel = cancel.clone();
+            async move {
+                tokio::time::sleep(STUCK).await;
+                cancel.cancel();
+            }
+        });
+        let next = processor.process_generation_cancellable(Arc::new(grid.clone()), &cancel)
+            .await
+            .expect("generation did not finish");
+        watchdog.abort();
+
+        assert_eq!(next, expected(&grid, boundary));
+        grid = next;
+    }
+}
+
+/// Polls a generation once, far enough to start it but not to finish it
+async fn start<T>(generation: &mut (impl Future<Output = T> + Unpin)) {
+    std::future::poll_fn(|cx| {
+        assert!(Pin::new(&mut *generation).poll(cx).is_pending(), "generation finished on its first poll");
+        Poll::Ready(())
+    }).await;
+}
+
+#[tokio::test]
+async fn slices_resume_at_the_next_column() {
+    let (width, height) = (12, 5);
+    let grid = Arc::new(random_grid(width, height, 3));
+    let row = GRID_START + 2;
+    let mut coroutine = create_time_sliced_row_coroutine(row, width, Rule::default(), Boundary::Torus);
+
+    // With no budget every slice computes exactly one cell, carrying on from the last
+    for generation in 0..2 {
+        for col in GRID_START..=width {
+            let outcome = coroutine(grid.clone(), Duration::ZERO, false).await;
+            assert_eq!((outcome.start_col, outcome.end_col), (col, col + 1), "generation {}", generation);
+            assert_eq!(outcome.is_complete, col == width);
+            // Only the completing slice hands back the row
+            match outcome.result {
+                Some(result) => assert_eq!(result, expected(&grid, Boundary::Torus)[row]),
+                None => assert!(!outcome.is_complete),
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn partial_slices_share_the_row_so_far() {
+    let (width, height) = (6, 4);
+    let grid = Arc::new(random_grid(width, height, 5));
+    let row = GRID_START + 1;
+    let next = expected(&grid, Boundary::DeadBorder);
+    let mut coroutine = create_time_sliced_row_coroutine(row, width, Rule::default(), Boundary::DeadBorder);
+
+    for col in GRID_START..=width {
+        let outcome = coroutine(grid.clone(), Duration::ZERO, true).await;
+        let result = outcome.result.expect("asked for the row so far");
+        assert_eq!(result[GRID_START..=col], next[row][GRID_START..=col]);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn zero_budget_completes() {
+    let (width, height) = (40, 30);
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::Torus, Duration::ZERO);
+    run_generations(&mut processor, random_grid(width, height, 1), Boundary::Torus, 5).await;
+
+    // One cell per slice, so one slice per column of every row
+    let stats = processor.last_stats();
+    assert!(stats.rows.iter().all(|row| row.slices == width && row.expired == width - 1));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tiny_budget_completes() {
+    let (width, height) = (200, 60);
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::DeadBorder, Duration::from_micros(1));
+    run_generations(&mut processor, random_grid(width, height, 2), Boundary::DeadBorder, 5).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn zero_budget_completes_in_bands() {
+    let (width, height) = (50, 40);
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::Torus, Duration::ZERO);
+    processor.set_threads(4);
+    run_generations(&mut processor, random_grid(width, height, 4), Boundary::Torus, 3).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dropping_a_generation_in_bands_keeps_the_processor_usable() {
+    let (width, height) = (60, 40);
+    let (abandoned, grid) = (random_grid(width, height, 6), random_grid(width, height, 16));
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::Torus, Duration::ZERO);
+    processor.set_threads(4);
+
+    // The bands are out on their workers when the generation is dropped
+    let mut generation = Box::pin(processor.process_generation(Arc::new(abandoned)));
+    start(&mut generation).await;
+    drop(generation);
+
+    run_generations(&mut processor, grid, Boundary::Torus, 3).await;
+}
+
+#[tokio::test]
+async fn cancelling_mid_row_starts_the_next_generation_afresh() {
+    let (width, height) = (30, 20);
+    let (abandoned, grid) = (random_grid(width, height, 7), random_grid(width, height, 17));
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::KleinBottle, Duration::ZERO);
+
+    // One cell into the first row, then cancelled before the next slice
+    let cancel = CancelToken::default();
+    let mut generation = Box::pin(processor.process_generation_cancellable(Arc::new(abandoned), &cancel));
+    start(&mut generation).await;
+    cancel.cancel();
+    assert!(generation.await.is_none());
+
+    run_generations(&mut processor, grid, Boundary::KleinBottle, 3).await;
+}
+
+#[tokio::test]
+async fn dropping_a_generation_mid_row_starts_the_next_afresh() {
+    let (width, height) = (30, 20);
+    let (abandoned, grid) = (random_grid(width, height, 8), random_grid(width, height, 18));
+    let mut processor = GenerationProcessor::new(width, height, Rule::default(), Boundary::Torus, Duration::ZERO);
+
+    let mut generation = Box::pin(processor.process_generation(Arc::new(abandoned)));
+    start(&mut generation).await;
+    drop(generation);
+
+    run_generations(&mut processor, grid, Boundary::Torus, 3).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn processors_sharing_a_scheduler_match_the_closure_engine() {
+    let shared = SharedScheduler::new(&tokio::runtime::Handle::current(), 2);
+    let sharing = |boundary, threads| {
+        let mut processor = GenerationProcessor::new(40, 30, Rule::default(), boundary, Duration::ZERO);
+        processor.set_threads(threads);
+        processor.set_shared_scheduler(Some(shared.clone()));
+        processor
+    };
+    let (mut torus, mut klein_bottle) = (sharing(Boundary::Torus, 1), sharing(Boundary::KleinBottle, 3));
+    tokio::join!(
+        run_generations(&mut torus, random_grid(40, 30, 9), Boundary::Torus, 3),
+        run_generations(&mut klein_bottle, random_grid(40, 30, 19), Boundary::KleinBottle, 3),
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn shared_scheduler_interleaves_the_rows_of_every_processor() {
+    // One driver, so a long generation queued first would hold up a short one unless their slices take turns
+    let shared = SharedScheduler::new(&tokio::runtime::Handle::current(), 1);
+    let mut long = GenerationProcessor::new(100, 100, Rule::default(), Boundary::Torus, Duration::ZERO);
+    let mut short = GenerationProcessor::new(3, 3, Rule::default(), Boundary::Torus, Duration::ZERO);
+    long.set_shared_scheduler(Some(shared.clone()));
+    short.set_shared_scheduler(Some(shared));
+
+    let finished = |generation| async move {
+        generation.await;
+        Instant::now()
+    };
+    let (long_done, short_done) = tokio::join!(
+        finished(long.process_generation(Arc::new(random_grid(100, 100, 10)))),
+        finished(short.process_generation(Arc::new(random_grid(3, 3, 11)))),
+    );
+    assert!(short_done < long_done);
+}
@@ -0,0 +1,118 @@
+// simulation_thread.rs - Edits cancel the generation in flight without stopping the ones after
+// Regression test for a cancel that landed after the thread had already reset for its edit, which left every later generation cancelled
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use life_core::{Boundary, CancelToken, Command, Cycle, GameOfLifeInterface, Rule, Simulation, SimulationHandle, SimulationThread};
+
+/// Longest a command may take to be applied and published
+const STUCK: Duration = Duration::from_secs(20);
+
+/// A game that only counts its generations, giving up on one whenever its token is cancelled
+struct Counter {
+    generation : u64,
+    is_running : bool,
+    cancel     : CancelToken,
+    handle     : Option<SimulationHandle<Counter>>,
+}
+
+impl GameOfLifeInterface for Counter {
+    fn update_generation(&mut self) {
+        if self.cancel.is_cancelled() { return; }
+        self.generation += 1;
+    }
+
+    fn hash_grid(&self) -> u64 { self.generation }
+    fn check_for_cycle(&mut self) -> Option<Cycle> { None }
+    fn clear_grid(&mut self) {}
+    fn apply_selected_pattern(&mut self) {}
+    fn set_rule(&mut self, _rule: Rule) {}
+    fn set_boundary(&mut self, _boundary: Boundary) {}
+    fn check_border_cells_dead(&self) -> bool { true }
+}
+
+impl Simulation for Counter {
+    type Snapshot = u64;
+
+    fn snapshot(&self) -> u64 { self.generation }
+    fn is_running(&self) -> bool { self.is_running }
+    fn set_running(&mut self, running: bool) { self.is_running = running; }
+    fn update_interval(&self) -> Duration { Duration::from_millis(1) }
+    fn toggle_cell(&mut self, _display_row: usize, _display_col: usize) {}
+    fn load_pattern(&mut self, _index: usize) {}
+
+    fn cancel_token(&self) -> Option<CancelToken> {
+        Some(self.cancel.clone())
+    }
+
+    fn attach(&mut self, handle: SimulationHandle<Counter>) {
+        self.handle = Some(handle);
+    }
+}
+
+fn spawn() -> (SimulationThread<Counter>, Receiver<()>) {
+    let (published, publishes) = mpsc::channel();
+    let game = Counter { generation: 0, is_running: false, cancel: CancelToken::default(), handle: None };
+    (SimulationThread::spawn(game, move || { let _ = published.send(()); }), publishes)
+}
+
+/// Waits for the thread to publish after each of `commands` commands
+fn await_publishes(publishes: &Receiver<()>, commands: usize) {
+    for _ in 0..commands {
+        publishes.recv_timeout(STUCK).expect("command was not applied");
+    }
+}
+
+#[test]
+fn step_after_edit_while_paused_advances() {
+    let (simulation, publishes) = spawn();
+
+    // The thread wakes for each edit while the UI is still sending, racing its reset against the cancel
+    for generation in 1..=500 {
+        simulation.edit(|_| {});
+        simulation.send(Command::Step);
+        await_publishes(&publishes, 2);
+        assert_eq!(*simulation.snapshot(), generation, "step after an edit did not advance");
+    }
+}
+
+#[test]
+fn running_after_edit_keeps_advancing() {
+    let (simulation, publishes) = spawn();
+    simulation.send(Command::ToggleCell { row: 0, col: 0 });
+    simulation.send(Command::Start);
+    await_publishes(&publishes, 2);
+
+    // Generations come every millisecond, a few publishes later the count has moved on
+    let started = *simulation.snapshot();
+    await_publishes(&publishes, 10);
+    assert!(*simulation.snapshot() > started, "running game stopped advancing after an edit");
+}
+
+#[test]
+fn background_work_is_applied_unless_cancelled_first() {
+    let (simulation, publishes) = spawn();
+
+    // Applying the result is a command of its own, published after the one that started the work
+    simulation.apply(|counter| {
+        let handle = counter.handle.clone().expect("handle attached on spawn");
+        handle.spawn_work(CancelToken::default(), |_| Some(41), |counter, generation| counter.generation = generation);
+    });
+    await_publishes(&publishes, 2);
+    assert_eq!(*simulation.snapshot(), 41);
+
+    // Work cancelled before its result reaches the thread still finishes, but changes nothing
+    let cancel = CancelToken::default();
+    let (release, released) = mpsc::channel::<()>();
+    let work_cancel = cancel.clone();
+    simulation.apply(move |counter| {
+        let handle = counter.handle.clone().expect("handle attached on spawn");
+        handle.spawn_work(work_cancel, move |_| released.recv().ok().map(|_| 7), |counter, generation| counter.generation = generation);
+    });
+    await_publishes(&publishes, 1);
+    cancel.cancel();
+    release.send(()).unwrap();
+    await_publishes(&publishes, 1);
+    assert_eq!(*simulation.snapshot(), 41);
+}